@@ -0,0 +1,49 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+//! Measures the round-trip latency of a `REQ_STATUS_BITS → GET_STATUS_BITS` request.
+//!
+//! Pass the serial number of a connected KDC101 to measure a real device. Without arguments, an
+//! in-process simulated device is used instead, which measures the overhead of Thormotion itself.
+//!
+//! ```bash
+//! cargo run --release --example latency [SERIAL_NUMBER]
+//! ```
+
+use std::time::{Duration, Instant};
+
+use thormotion::devices::KDC101;
+
+/// Number of requests to measure.
+const SAMPLES: usize = 1000;
+
+fn main() -> Result<(), thormotion::Error> {
+    let mut device = match std::env::args().nth(1) {
+        Some(serial_number) => KDC101::new(serial_number)?,
+        None => KDC101::simulated("27000000")?,
+    };
+    device.open()?;
+    device.get_status_bits()?; // Warm up
+    let mut samples = (0..SAMPLES)
+        .map(|_| {
+            let start = Instant::now();
+            device.get_status_bits().map(|_| start.elapsed())
+        })
+        .collect::<Result<Vec<Duration>, _>>()?;
+    samples.sort();
+    let percentile = |p: f64| samples[((SAMPLES - 1) as f64 * p).round() as usize];
+    println!("{device} GET_STATUS_BITS round trip ({SAMPLES} samples)");
+    println!("  min    {:?}", samples[0]);
+    println!("  median {:?}", percentile(0.50));
+    println!("  p99    {:?}", percentile(0.99));
+    println!("  max    {:?}", samples[SAMPLES - 1]);
+    device.close()?;
+    Ok(())
+}
@@ -0,0 +1,131 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use crate::apt::{DEVICE, HOST, Message};
+
+/// Identifier for the "motherboard" of a card slot system (Thorlabs APT Protocol, Issue 39,
+/// Page 35).
+///
+/// Messages which refer to the whole card slot system use [`MOTHERBOARD`] as the destination byte.
+pub const MOTHERBOARD: u8 = 0x11;
+
+/// Offset for "bays in a card slot system" (Thorlabs APT Protocol, Issue 39, Page 35).
+///
+/// The first bay is addressed as `0x21`, the second bay as `0x22`, etc. Each bay corresponds to a
+/// single channel of a benchtop controller or modular rack.
+pub const BAY: u8 = 0x20;
+
+/// The highest bay number in a card slot system.
+const BAY_COUNT: u8 = 10;
+
+/// How the host addresses the channels of a Thorlabs device.
+///
+/// ```
+/// use thormotion::apt::{Addressing, Message};
+///
+/// let message = Message::MotReqStatusBits { channel: 2 };
+/// let bytes = Addressing::Bays.encode(&message, 2);
+/// assert_eq!(bytes, Some(vec![0x29, 0x04, 0x01, 0x00, 0x22, 0x01]));
+/// assert_eq!(
+///     Addressing::Bays.channel(&[0x2A, 0x04, 0x0C, 0x00, 0x81, 0x22]),
+///     Some(2)
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Addressing {
+    /// Every message is sent to a generic USB [`DEVICE`]. Channels are identified by the channel
+    /// ident.
+    #[default]
+    Generic,
+    /// Each channel is a bay in a card slot system (e.g. a benchtop controller with plug-in cards,
+    /// or a modular rack). Channel `n` is addressed as [`BAY`]` + n` with channel ident `1`.
+    /// Messages which refer to the whole device are sent to the [`MOTHERBOARD`].
+    Bays,
+}
+
+impl Addressing {
+    /// Returns the destination byte for messages sent to the specified channel. Channel `0`
+    /// refers to the whole device.
+    ///
+    /// Returns [`None`] if the channel cannot be addressed. For [`Bays`][1], channels are
+    /// numbered from one up to the number of bays in a card slot system.
+    ///
+    /// [1]: Addressing::Bays
+    pub fn destination(self, channel: usize) -> Option<u8> {
+        match (self, channel) {
+            (Addressing::Generic, _) => Some(DEVICE),
+            (Addressing::Bays, 0) => Some(MOTHERBOARD),
+            (Addressing::Bays, n) => u8::try_from(n)
+                .ok()
+                .filter(|n| *n <= BAY_COUNT)
+                .and_then(|n| BAY.checked_add(n)),
+        }
+    }
+
+    /// Encodes a message sent from the [`HOST`] to the specified channel. Channel `0` refers to
+    /// the whole device.
+    ///
+    /// For [`Bays`][1], the channel ident of the message is replaced by `1`.
+    ///
+    /// Returns [`None`] if the channel cannot be addressed. See [`destination`][2].
+    ///
+    /// [1]: Addressing::Bays
+    /// [2]: Addressing::destination
+    pub fn encode(self, message: &Message, channel: usize) -> Option<Vec<u8>> {
+        match self {
+            Addressing::Generic => Some(message.encode(DEVICE, HOST)),
+            Addressing::Bays => Some(
+                message
+                    .clone()
+                    .with_channel(1)
+                    .encode(self.destination(channel)?, HOST),
+            ),
+        }
+    }
+
+    /// Returns the channel that a message received from the device refers to. Channels are
+    /// numbered from one. Returns `0` if the message refers to the whole device.
+    ///
+    /// Returns [`None`] if the message is too short to contain a channel ident.
+    ///
+    /// Messages sent from a bay are routed using the [`bay`] source byte. Otherwise, for
+    /// [`Bays`][1], the message refers to the whole device. For [`Generic`][2] units, the channel
+    /// ident is given by the first header parameter for header-only messages, or by the first two
+    /// bytes of the data packet for header-plus-payload messages.
+    ///
+    /// [1]: Addressing::Bays
+    /// [2]: Addressing::Generic
+    pub fn channel(self, message: &[u8]) -> Option<usize> {
+        if let Some(bay) = bay(message) {
+            return Some(bay);
+        }
+        match self {
+            Addressing::Bays => message.get(5).map(|_| 0), // Sent from the motherboard
+            Addressing::Generic => match message.get(4)? & 0x80 {
+                0 => message.get(2).map(|ident| *ident as usize),
+                _ => message
+                    .get(6..8)
+                    .map(|ident| u16::from_le_bytes([ident[0], ident[1]]) as usize),
+            },
+        }
+    }
+}
+
+/// Returns the bay that the message was sent from, if the source byte is `0x21` for the first bay,
+/// `0x22` for the second bay, etc. Bays are numbered from one.
+///
+/// Returns [`None`] if the message was not sent from a bay in a card slot system, or if the message
+/// is too short to contain a source byte.
+pub fn bay(message: &[u8]) -> Option<usize> {
+    match message.get(5)?.checked_sub(BAY) {
+        Some(bay @ 1..=BAY_COUNT) => Some(bay as usize),
+        _ => None,
+    }
+}
@@ -0,0 +1,59 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+//! Two-byte message IDs from the Thorlabs APT Protocol, Issue 39.
+//!
+//! IDs are stored in transmission order (little-endian). For example, `MGMSG_MOT_MOVE_HOME` is
+//! `0x0443` in the protocol specification and `[0x43, 0x04]` on the wire.
+
+/* ------------------------------------------------------------------------------------------ HW */
+
+pub const HW_DISCONNECT: [u8; 2] = [0x02, 0x00];
+pub const HW_START_UPDATEMSGS: [u8; 2] = [0x11, 0x00];
+pub const HW_STOP_UPDATEMSGS: [u8; 2] = [0x12, 0x00];
+pub const HW_RESPONSE: [u8; 2] = [0x80, 0x00];
+pub const HW_RICHRESPONSE: [u8; 2] = [0x81, 0x00];
+
+/* ----------------------------------------------------------------------------------------- MOD */
+
+pub const MOD_SET_CHANENABLESTATE: [u8; 2] = [0x10, 0x02];
+pub const MOD_REQ_CHANENABLESTATE: [u8; 2] = [0x11, 0x02];
+pub const MOD_GET_CHANENABLESTATE: [u8; 2] = [0x12, 0x02];
+pub const MOD_IDENTIFY: [u8; 2] = [0x23, 0x02];
+
+/* ----------------------------------------------------------------------------------------- MOT */
+
+pub const MOT_SET_VELPARAMS: [u8; 2] = [0x13, 0x04];
+pub const MOT_REQ_VELPARAMS: [u8; 2] = [0x14, 0x04];
+pub const MOT_GET_VELPARAMS: [u8; 2] = [0x15, 0x04];
+pub const MOT_SET_JOGPARAMS: [u8; 2] = [0x16, 0x04];
+pub const MOT_REQ_JOGPARAMS: [u8; 2] = [0x17, 0x04];
+pub const MOT_GET_JOGPARAMS: [u8; 2] = [0x18, 0x04];
+pub const MOT_REQ_STATUSBITS: [u8; 2] = [0x29, 0x04];
+pub const MOT_GET_STATUSBITS: [u8; 2] = [0x2A, 0x04];
+pub const MOT_SET_LIMSWITCHPARAMS: [u8; 2] = [0x23, 0x04];
+pub const MOT_REQ_LIMSWITCHPARAMS: [u8; 2] = [0x24, 0x04];
+pub const MOT_GET_LIMSWITCHPARAMS: [u8; 2] = [0x25, 0x04];
+pub const MOT_SET_HOMEPARAMS: [u8; 2] = [0x40, 0x04];
+pub const MOT_REQ_HOMEPARAMS: [u8; 2] = [0x41, 0x04];
+pub const MOT_GET_HOMEPARAMS: [u8; 2] = [0x42, 0x04];
+pub const MOT_MOVE_HOME: [u8; 2] = [0x43, 0x04];
+pub const MOT_MOVE_HOMED: [u8; 2] = [0x44, 0x04];
+pub const MOT_SET_MOVERELPARAMS: [u8; 2] = [0x45, 0x04];
+pub const MOT_MOVE_RELATIVE: [u8; 2] = [0x48, 0x04];
+pub const MOT_SET_MOVEABSPARAMS: [u8; 2] = [0x50, 0x04];
+pub const MOT_MOVE_ABSOLUTE: [u8; 2] = [0x53, 0x04];
+pub const MOT_MOVE_JOG: [u8; 2] = [0x6A, 0x04];
+pub const MOT_MOVE_COMPLETED: [u8; 2] = [0x64, 0x04];
+pub const MOT_MOVE_STOP: [u8; 2] = [0x65, 0x04];
+pub const MOT_MOVE_STOPPED: [u8; 2] = [0x66, 0x04];
+pub const MOT_REQ_USTATUSUPDATE: [u8; 2] = [0x90, 0x04];
+pub const MOT_GET_USTATUSUPDATE: [u8; 2] = [0x91, 0x04];
+pub const MOT_ACK_USTATUSUPDATE: [u8; 2] = [0x92, 0x04];
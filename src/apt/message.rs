@@ -0,0 +1,433 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use crate::apt::{
+    ChanEnableState,
+    Direction,
+    HomeParams,
+    JogParams,
+    LimSwitchParams,
+    Move,
+    RichResponse,
+    StatusBits,
+    StopMode,
+    UStatus,
+    VelParams,
+    id,
+    length,
+};
+use crate::error::apt::Error;
+
+/// A Thorlabs APT message.
+///
+/// Header-only messages carry their parameters in the six-byte header. Header-plus-payload
+/// messages carry a typed data packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /* --------------------------------------------------------------------------------------
+     * HW */
+    /// Reports that the device is about to disconnect from the USB bus.
+    HwDisconnect,
+    /// Starts unsolicited status update messages.
+    HwStartUpdateMsgs,
+    /// Stops unsolicited status update messages.
+    HwStopUpdateMsgs,
+    /// Reports an unexpected event which requires user intervention.
+    HwResponse { code: u16 },
+    /// Reports an unexpected event, with an error code and description.
+    HwRichResponse(RichResponse),
+    /* -------------------------------------------------------------------------------------
+     * MOD */
+    /// Enables or disables a channel.
+    ModSetChanEnableState(ChanEnableState),
+    /// Requests the channel enable state.
+    ModReqChanEnableState { channel: u8 },
+    /// Reports the channel enable state.
+    ModGetChanEnableState(ChanEnableState),
+    /// Flashes the front panel LED.
+    ModIdentify { channel: u8 },
+    /* -------------------------------------------------------------------------------------
+     * MOT */
+    /// Sets the velocity profile.
+    MotSetVelParams(VelParams),
+    /// Requests the velocity profile.
+    MotReqVelParams { channel: u8 },
+    /// Reports the velocity profile.
+    MotGetVelParams(VelParams),
+    /// Sets the jog parameters.
+    MotSetJogParams(JogParams),
+    /// Requests the jog parameters.
+    MotReqJogParams { channel: u8 },
+    /// Reports the jog parameters.
+    MotGetJogParams(JogParams),
+    /// Requests the status bits.
+    MotReqStatusBits { channel: u8 },
+    /// Reports the status bits.
+    MotGetStatusBits(StatusBits),
+    /// Sets the limit switch parameters.
+    MotSetLimSwitchParams(LimSwitchParams),
+    /// Requests the limit switch parameters.
+    MotReqLimSwitchParams { channel: u8 },
+    /// Reports the limit switch parameters.
+    MotGetLimSwitchParams(LimSwitchParams),
+    /// Sets the homing parameters.
+    MotSetHomeParams(HomeParams),
+    /// Requests the homing parameters.
+    MotReqHomeParams { channel: u8 },
+    /// Reports the homing parameters.
+    MotGetHomeParams(HomeParams),
+    /// Starts a homing move.
+    MotMoveHome { channel: u8 },
+    /// Reports that a homing move has completed.
+    MotMoveHomed { channel: u8 },
+    /// Sets the stored relative move distance.
+    MotSetMoveRelParams(Move),
+    /// Starts a relative move by the specified distance.
+    MotMoveRelative(Move),
+    /// Starts a relative move by the stored relative move distance.
+    MotMoveRelativeFromParams { channel: u8 },
+    /// Sets the stored absolute move position.
+    MotSetMoveAbsParams(Move),
+    /// Starts an absolute move to the specified position.
+    MotMoveAbsolute(Move),
+    /// Starts an absolute move to the stored absolute move position.
+    MotMoveAbsoluteFromParams { channel: u8 },
+    /// Starts a jog move in the specified direction, using the stored jog parameters.
+    MotMoveJog { channel: u8, direction: Direction },
+    /// Reports that a relative or absolute move has completed.
+    MotMoveCompleted(UStatus),
+    /// Stops the current move.
+    MotMoveStop { channel: u8, mode: StopMode },
+    /// Reports that a move has stopped.
+    MotMoveStopped(UStatus),
+    /// Requests the motor status.
+    MotReqUStatusUpdate { channel: u8 },
+    /// Reports the motor status.
+    MotGetUStatusUpdate(UStatus),
+    /// Acknowledges status update messages. Keeps update messages enabled.
+    MotAckUStatusUpdate,
+}
+
+/// The contents of a message following the message ID.
+enum Body {
+    /// Two header parameter bytes.
+    Params([u8; 2]),
+    /// A variable-length data packet.
+    Data(Vec<u8>),
+}
+
+impl Message {
+    /// Returns the two-byte message ID.
+    pub fn id(&self) -> [u8; 2] {
+        match self {
+            Message::HwDisconnect => id::HW_DISCONNECT,
+            Message::HwStartUpdateMsgs => id::HW_START_UPDATEMSGS,
+            Message::HwStopUpdateMsgs => id::HW_STOP_UPDATEMSGS,
+            Message::HwResponse { .. } => id::HW_RESPONSE,
+            Message::HwRichResponse(_) => id::HW_RICHRESPONSE,
+            Message::ModSetChanEnableState(_) => id::MOD_SET_CHANENABLESTATE,
+            Message::ModReqChanEnableState { .. } => id::MOD_REQ_CHANENABLESTATE,
+            Message::ModGetChanEnableState(_) => id::MOD_GET_CHANENABLESTATE,
+            Message::ModIdentify { .. } => id::MOD_IDENTIFY,
+            Message::MotSetVelParams(_) => id::MOT_SET_VELPARAMS,
+            Message::MotReqVelParams { .. } => id::MOT_REQ_VELPARAMS,
+            Message::MotGetVelParams(_) => id::MOT_GET_VELPARAMS,
+            Message::MotSetJogParams(_) => id::MOT_SET_JOGPARAMS,
+            Message::MotReqJogParams { .. } => id::MOT_REQ_JOGPARAMS,
+            Message::MotGetJogParams(_) => id::MOT_GET_JOGPARAMS,
+            Message::MotReqStatusBits { .. } => id::MOT_REQ_STATUSBITS,
+            Message::MotGetStatusBits(_) => id::MOT_GET_STATUSBITS,
+            Message::MotSetLimSwitchParams(_) => id::MOT_SET_LIMSWITCHPARAMS,
+            Message::MotReqLimSwitchParams { .. } => id::MOT_REQ_LIMSWITCHPARAMS,
+            Message::MotGetLimSwitchParams(_) => id::MOT_GET_LIMSWITCHPARAMS,
+            Message::MotSetHomeParams(_) => id::MOT_SET_HOMEPARAMS,
+            Message::MotReqHomeParams { .. } => id::MOT_REQ_HOMEPARAMS,
+            Message::MotGetHomeParams(_) => id::MOT_GET_HOMEPARAMS,
+            Message::MotMoveHome { .. } => id::MOT_MOVE_HOME,
+            Message::MotMoveHomed { .. } => id::MOT_MOVE_HOMED,
+            Message::MotSetMoveRelParams(_) => id::MOT_SET_MOVERELPARAMS,
+            Message::MotMoveRelative(_) => id::MOT_MOVE_RELATIVE,
+            Message::MotMoveRelativeFromParams { .. } => id::MOT_MOVE_RELATIVE,
+            Message::MotSetMoveAbsParams(_) => id::MOT_SET_MOVEABSPARAMS,
+            Message::MotMoveAbsolute(_) => id::MOT_MOVE_ABSOLUTE,
+            Message::MotMoveAbsoluteFromParams { .. } => id::MOT_MOVE_ABSOLUTE,
+            Message::MotMoveJog { .. } => id::MOT_MOVE_JOG,
+            Message::MotMoveCompleted(_) => id::MOT_MOVE_COMPLETED,
+            Message::MotMoveStop { .. } => id::MOT_MOVE_STOP,
+            Message::MotMoveStopped(_) => id::MOT_MOVE_STOPPED,
+            Message::MotReqUStatusUpdate { .. } => id::MOT_REQ_USTATUSUPDATE,
+            Message::MotGetUStatusUpdate(_) => id::MOT_GET_USTATUSUPDATE,
+            Message::MotAckUStatusUpdate => id::MOT_ACK_USTATUSUPDATE,
+        }
+    }
+
+    /// Returns the header parameters or data packet.
+    fn body(&self) -> Body {
+        match self {
+            Message::HwDisconnect
+            | Message::HwStartUpdateMsgs
+            | Message::HwStopUpdateMsgs
+            | Message::MotAckUStatusUpdate => Body::Params([0, 0]),
+            Message::HwResponse { code } => Body::Params(code.to_le_bytes()),
+            Message::HwRichResponse(response) => Body::Data(response.to_bytes()),
+            Message::ModSetChanEnableState(state) | Message::ModGetChanEnableState(state) => {
+                Body::Params(state.params())
+            }
+            Message::ModReqChanEnableState { channel }
+            | Message::ModIdentify { channel }
+            | Message::MotReqVelParams { channel }
+            | Message::MotReqJogParams { channel }
+            | Message::MotReqStatusBits { channel }
+            | Message::MotReqLimSwitchParams { channel }
+            | Message::MotReqHomeParams { channel }
+            | Message::MotMoveHome { channel }
+            | Message::MotMoveHomed { channel }
+            | Message::MotMoveRelativeFromParams { channel }
+            | Message::MotMoveAbsoluteFromParams { channel }
+            | Message::MotReqUStatusUpdate { channel } => Body::Params([*channel, 0]),
+            Message::MotSetLimSwitchParams(params) | Message::MotGetLimSwitchParams(params) => {
+                Body::Data(params.to_bytes())
+            }
+            Message::MotSetHomeParams(params) | Message::MotGetHomeParams(params) => {
+                Body::Data(params.to_bytes())
+            }
+            Message::MotSetJogParams(params) | Message::MotGetJogParams(params) => {
+                Body::Data(params.to_bytes())
+            }
+            Message::MotMoveJog { channel, direction } => {
+                Body::Params([*channel, direction.param()])
+            }
+            Message::MotSetVelParams(params) | Message::MotGetVelParams(params) => {
+                Body::Data(params.to_bytes())
+            }
+            Message::MotGetStatusBits(status) => Body::Data(status.to_bytes()),
+            Message::MotSetMoveRelParams(data)
+            | Message::MotMoveRelative(data)
+            | Message::MotSetMoveAbsParams(data)
+            | Message::MotMoveAbsolute(data) => Body::Data(data.to_bytes()),
+            Message::MotMoveStop { channel, mode } => Body::Params([*channel, mode.param()]),
+            Message::MotMoveCompleted(status)
+            | Message::MotMoveStopped(status)
+            | Message::MotGetUStatusUpdate(status) => Body::Data(status.to_bytes()),
+        }
+    }
+
+    /// Returns the message with its channel ident replaced. Messages without a channel ident are
+    /// returned unchanged.
+    pub fn with_channel(mut self, ident: u8) -> Self {
+        match &mut self {
+            Message::HwDisconnect
+            | Message::HwStartUpdateMsgs
+            | Message::HwStopUpdateMsgs
+            | Message::HwResponse { .. }
+            | Message::HwRichResponse(_)
+            | Message::MotAckUStatusUpdate => {}
+            Message::ModSetChanEnableState(state) | Message::ModGetChanEnableState(state) => {
+                state.channel = ident;
+            }
+            Message::ModReqChanEnableState { channel }
+            | Message::ModIdentify { channel }
+            | Message::MotReqVelParams { channel }
+            | Message::MotReqJogParams { channel }
+            | Message::MotReqStatusBits { channel }
+            | Message::MotReqLimSwitchParams { channel }
+            | Message::MotReqHomeParams { channel }
+            | Message::MotMoveHome { channel }
+            | Message::MotMoveHomed { channel }
+            | Message::MotMoveRelativeFromParams { channel }
+            | Message::MotMoveAbsoluteFromParams { channel }
+            | Message::MotMoveJog { channel, .. }
+            | Message::MotMoveStop { channel, .. }
+            | Message::MotReqUStatusUpdate { channel } => *channel = ident,
+            Message::MotSetVelParams(params) | Message::MotGetVelParams(params) => {
+                params.channel = ident as u16;
+            }
+            Message::MotSetJogParams(params) | Message::MotGetJogParams(params) => {
+                params.channel = ident as u16;
+            }
+            Message::MotSetLimSwitchParams(params) | Message::MotGetLimSwitchParams(params) => {
+                params.channel = ident as u16;
+            }
+            Message::MotSetHomeParams(params) | Message::MotGetHomeParams(params) => {
+                params.channel = ident as u16;
+            }
+            Message::MotGetStatusBits(status) => status.channel = ident as u16,
+            Message::MotSetMoveRelParams(data)
+            | Message::MotMoveRelative(data)
+            | Message::MotSetMoveAbsParams(data)
+            | Message::MotMoveAbsolute(data) => data.channel = ident as u16,
+            Message::MotMoveCompleted(status)
+            | Message::MotMoveStopped(status)
+            | Message::MotGetUStatusUpdate(status) => status.channel = ident as u16,
+        }
+        self
+    }
+
+    /// Encodes the message with the specified destination and source bytes.
+    ///
+    /// For header-plus-payload messages, the most significant bit of the destination byte is set
+    /// automatically.
+    pub(super) fn encode(&self, destination: u8, source: u8) -> Vec<u8> {
+        let id = self.id();
+        match self.body() {
+            Body::Params(params) => vec![id[0], id[1], params[0], params[1], destination, source],
+            Body::Data(data) => [
+                id.as_slice(),
+                &(data.len() as u16).to_le_bytes(),
+                &[destination | 0x80, source],
+                &data,
+            ]
+            .concat(),
+        }
+    }
+
+    /// Decodes a complete message. See [`decode`][1].
+    ///
+    /// [1]: crate::apt::decode
+    pub(super) fn decode(message: &[u8]) -> Result<Self, Error> {
+        let Some(header) = message.first_chunk::<6>() else {
+            return Err(Error::Truncated(message.len()));
+        };
+        let id = [header[0], header[1]];
+        let params = [header[2], header[3]];
+        let expected = length(header);
+        if message.len() != expected {
+            return Err(Error::InvalidLength {
+                id,
+                expected,
+                actual: message.len(),
+            });
+        }
+        let data = &message[6..];
+        // Returns the data packet if it has the expected length
+        let sized = |n: usize| match data.len() == n {
+            true => Ok(data),
+            false => Err(Error::InvalidLength {
+                id,
+                expected: 6 + n,
+                actual: message.len(),
+            }),
+        };
+        let channel = params[0];
+        let message = match id {
+            id::HW_DISCONNECT => sized(0).map(|_| Message::HwDisconnect)?,
+            id::HW_START_UPDATEMSGS => sized(0).map(|_| Message::HwStartUpdateMsgs)?,
+            id::HW_STOP_UPDATEMSGS => sized(0).map(|_| Message::HwStopUpdateMsgs)?,
+            id::HW_RESPONSE => {
+                sized(0)?;
+                Message::HwResponse {
+                    code: u16::from_le_bytes(params),
+                }
+            }
+            id::HW_RICHRESPONSE => {
+                let data = sized(RichResponse::LENGTH)?;
+                Message::HwRichResponse(RichResponse::from_bytes(data))
+            }
+            id::MOD_SET_CHANENABLESTATE => {
+                sized(0)?;
+                Message::ModSetChanEnableState(ChanEnableState::from_params(id, params)?)
+            }
+            id::MOD_REQ_CHANENABLESTATE => {
+                sized(0).map(|_| Message::ModReqChanEnableState { channel })?
+            }
+            id::MOD_GET_CHANENABLESTATE => {
+                sized(0)?;
+                Message::ModGetChanEnableState(ChanEnableState::from_params(id, params)?)
+            }
+            id::MOD_IDENTIFY => sized(0).map(|_| Message::ModIdentify { channel })?,
+            id::MOT_SET_VELPARAMS => {
+                Message::MotSetVelParams(VelParams::from_bytes(sized(VelParams::LENGTH)?))
+            }
+            id::MOT_REQ_VELPARAMS => sized(0).map(|_| Message::MotReqVelParams { channel })?,
+            id::MOT_GET_VELPARAMS => {
+                Message::MotGetVelParams(VelParams::from_bytes(sized(VelParams::LENGTH)?))
+            }
+            id::MOT_SET_JOGPARAMS => {
+                Message::MotSetJogParams(JogParams::from_bytes(id, sized(JogParams::LENGTH)?)?)
+            }
+            id::MOT_REQ_JOGPARAMS => sized(0).map(|_| Message::MotReqJogParams { channel })?,
+            id::MOT_GET_JOGPARAMS => {
+                Message::MotGetJogParams(JogParams::from_bytes(id, sized(JogParams::LENGTH)?)?)
+            }
+            id::MOT_REQ_STATUSBITS => sized(0).map(|_| Message::MotReqStatusBits { channel })?,
+            id::MOT_GET_STATUSBITS => {
+                let data = sized(StatusBits::LENGTH)?;
+                Message::MotGetStatusBits(StatusBits::from_bytes(data))
+            }
+            id::MOT_SET_LIMSWITCHPARAMS => {
+                let data = sized(LimSwitchParams::LENGTH)?;
+                Message::MotSetLimSwitchParams(LimSwitchParams::from_bytes(id, data)?)
+            }
+            id::MOT_REQ_LIMSWITCHPARAMS => {
+                sized(0).map(|_| Message::MotReqLimSwitchParams { channel })?
+            }
+            id::MOT_GET_LIMSWITCHPARAMS => {
+                let data = sized(LimSwitchParams::LENGTH)?;
+                Message::MotGetLimSwitchParams(LimSwitchParams::from_bytes(id, data)?)
+            }
+            id::MOT_SET_HOMEPARAMS => {
+                Message::MotSetHomeParams(HomeParams::from_bytes(id, sized(HomeParams::LENGTH)?)?)
+            }
+            id::MOT_REQ_HOMEPARAMS => sized(0).map(|_| Message::MotReqHomeParams { channel })?,
+            id::MOT_GET_HOMEPARAMS => {
+                Message::MotGetHomeParams(HomeParams::from_bytes(id, sized(HomeParams::LENGTH)?)?)
+            }
+            id::MOT_MOVE_HOME => sized(0).map(|_| Message::MotMoveHome { channel })?,
+            id::MOT_MOVE_HOMED => sized(0).map(|_| Message::MotMoveHomed { channel })?,
+            id::MOT_SET_MOVERELPARAMS => {
+                Message::MotSetMoveRelParams(Move::from_bytes(sized(Move::LENGTH)?))
+            }
+            id::MOT_MOVE_RELATIVE if data.is_empty() => {
+                Message::MotMoveRelativeFromParams { channel }
+            }
+            id::MOT_MOVE_RELATIVE => {
+                Message::MotMoveRelative(Move::from_bytes(sized(Move::LENGTH)?))
+            }
+            id::MOT_SET_MOVEABSPARAMS => {
+                Message::MotSetMoveAbsParams(Move::from_bytes(sized(Move::LENGTH)?))
+            }
+            id::MOT_MOVE_ABSOLUTE if data.is_empty() => {
+                Message::MotMoveAbsoluteFromParams { channel }
+            }
+            id::MOT_MOVE_ABSOLUTE => {
+                Message::MotMoveAbsolute(Move::from_bytes(sized(Move::LENGTH)?))
+            }
+            id::MOT_MOVE_JOG => {
+                sized(0)?;
+                Message::MotMoveJog {
+                    channel,
+                    direction: Direction::from_param(id, params[1])?,
+                }
+            }
+            id::MOT_MOVE_COMPLETED => {
+                Message::MotMoveCompleted(UStatus::from_bytes(sized(UStatus::LENGTH)?))
+            }
+            id::MOT_MOVE_STOP => {
+                sized(0)?;
+                Message::MotMoveStop {
+                    channel,
+                    mode: StopMode::from_param(id, params[1])?,
+                }
+            }
+            id::MOT_MOVE_STOPPED => {
+                Message::MotMoveStopped(UStatus::from_bytes(sized(UStatus::LENGTH)?))
+            }
+            id::MOT_REQ_USTATUSUPDATE => {
+                sized(0).map(|_| Message::MotReqUStatusUpdate { channel })?
+            }
+            id::MOT_GET_USTATUSUPDATE => {
+                Message::MotGetUStatusUpdate(UStatus::from_bytes(sized(UStatus::LENGTH)?))
+            }
+            id::MOT_ACK_USTATUSUPDATE => sized(0).map(|_| Message::MotAckUStatusUpdate)?,
+            _ => return Err(Error::UnknownId(id)),
+        };
+        Ok(message)
+    }
+}
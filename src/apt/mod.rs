@@ -0,0 +1,129 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+//! Typed encoding and decoding of Thorlabs APT protocol messages.
+//!
+//! These are the same definitions that Thormotion uses to communicate with Thorlabs devices. They
+//! can be used to build additional tooling, such as sniffers, simulators, and analysers.
+//!
+//! ```
+//! use thormotion::apt::{self, Message};
+//!
+//! let bytes = apt::encode(&Message::MotReqStatusBits { channel: 1 });
+//! assert_eq!(bytes, [0x29, 0x04, 0x01, 0x00, 0x50, 0x01]);
+//! assert_eq!(
+//!     apt::decode(&bytes),
+//!     Ok(Message::MotReqStatusBits { channel: 1 })
+//! );
+//! ```
+
+/* ------------------------------------------------------------------------------ Public Modules */
+
+pub mod id;
+
+/* ----------------------------------------------------------------------------- Private Modules */
+
+mod address;
+mod message;
+mod payload;
+
+/* ------------------------------------------------------------------------------ Public Exports */
+
+pub use address::{Addressing, BAY, MOTHERBOARD, bay};
+pub use message::Message;
+pub use payload::{
+    ChanEnableState,
+    Direction,
+    HardLimit,
+    HomeParams,
+    JogMode,
+    JogParams,
+    LimSwitchParams,
+    LimitSwitch,
+    Move,
+    RichResponse,
+    SoftLimitMode,
+    StatusBits,
+    StopMode,
+    UStatus,
+    VelParams,
+};
+
+use crate::error::apt::Error;
+
+/// Identifier for "host" (Thorlabs APT Protocol, Issue 39, Page 35).
+///
+/// Messages sent to Thorlabs devices use [`HOST`] as the source byte.
+/// Messages sent from Thorlabs devices use [`HOST`] as the destination byte.
+pub const HOST: u8 = 0x01;
+
+/// Identifier for “generic USB units” (Thorlabs APT Protocol, Issue 39, Page 35).
+///
+/// Messages sent to Thorlabs devices use [`DEVICE`] as the destination byte.
+/// Messages sent from Thorlabs devices use [`DEVICE`] as the source byte.
+pub const DEVICE: u8 = 0x50;
+
+/// Encodes a message sent from the [`HOST`] to a generic USB [`DEVICE`].
+///
+/// To encode a message for a bay in a card slot system, see [`Addressing`]. To encode a message
+/// with different destination and source bytes (e.g. a response from a simulated device), see
+/// [`encode_addressed`].
+pub fn encode(message: &Message) -> Vec<u8> {
+    message.encode(DEVICE, HOST)
+}
+
+/// Encodes a message with the specified destination and source bytes.
+///
+/// For header-plus-payload messages, the most significant bit of the destination byte is set
+/// automatically.
+pub fn encode_addressed(message: &Message, destination: u8, source: u8) -> Vec<u8> {
+    message.encode(destination, source)
+}
+
+/// Decodes a complete message, including the six-byte header.
+///
+/// The destination and source bytes are not checked. Returns an [`Error`] if the message ID is
+/// unknown, if the message length does not match the header, or if the message contains an
+/// invalid value.
+pub fn decode(message: &[u8]) -> Result<Message, Error> {
+    Message::decode(message)
+}
+
+/// Checks the destination and source bytes of a message sent from a device to the [`HOST`].
+///
+/// The destination must be the [`HOST`]. The source must be a generic USB [`DEVICE`], the
+/// [`MOTHERBOARD`] of a card slot system, or a [`BAY`] (Thorlabs APT Protocol, Issue 39, Page 35).
+/// Returns [`Error::InvalidAddress`] otherwise.
+pub fn validate(header: &[u8; 6]) -> Result<(), Error> {
+    let destination = header[4] & 0x7F; // Ignore the header-plus-payload flag
+    let source = header[5];
+    let valid =
+        destination == HOST && (source == DEVICE || source == MOTHERBOARD || bay(header).is_some());
+    match valid {
+        true => Ok(()),
+        false => Err(Error::InvalidAddress {
+            id: [header[0], header[1]],
+            destination: header[4],
+            source,
+        }),
+    }
+}
+
+/// Returns the total length (number of bytes) of the message described by the six-byte header.
+///
+/// If the most significant bit of the destination byte is set, the header is followed by a data
+/// packet whose length is given by the little-endian `u16` in bytes two and three. Otherwise, the
+/// message is header-only (Thorlabs APT Protocol, Issue 39, Page 34).
+pub fn length(header: &[u8; 6]) -> usize {
+    match header[4] & 0x80 {
+        0 => 6,
+        _ => 6 + u16::from_le_bytes([header[2], header[3]]) as usize,
+    }
+}
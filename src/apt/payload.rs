@@ -0,0 +1,609 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use crate::apt::{Message, decode};
+use crate::error::apt::Error;
+
+/// Reads a little-endian `u16` from the first two bytes of the slice.
+fn word(data: &[u8]) -> u16 {
+    u16::from_le_bytes([data[0], data[1]])
+}
+
+/// Reads a little-endian `u32` from the first four bytes of the slice.
+fn long(data: &[u8]) -> u32 {
+    u32::from_le_bytes([data[0], data[1], data[2], data[3]])
+}
+
+/// The channel enable state carried by `MOD_SET_CHANENABLESTATE` and `MOD_GET_CHANENABLESTATE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChanEnableState {
+    /// The channel ident.
+    pub channel: u8,
+    /// `True` if the channel is enabled.
+    pub enabled: bool,
+}
+
+impl ChanEnableState {
+    /// Decodes a complete `MOD_GET_CHANENABLESTATE` message.
+    pub fn decode(message: &[u8]) -> Result<Self, Error> {
+        match decode(message)? {
+            Message::ModGetChanEnableState(state) => Ok(state),
+            other => Err(Error::UnexpectedMessage(other.id())),
+        }
+    }
+
+    /// Decodes the enable state from the header parameters.
+    pub(super) fn from_params(id: [u8; 2], params: [u8; 2]) -> Result<Self, Error> {
+        let enabled = match params[1] {
+            0x01 => true,
+            0x02 => false,
+            value => return Err(Error::InvalidValue { id, value }),
+        };
+        Ok(Self {
+            channel: params[0],
+            enabled,
+        })
+    }
+
+    /// Encodes the enable state into the header parameters.
+    pub(super) fn params(&self) -> [u8; 2] {
+        [self.channel, if self.enabled { 0x01 } else { 0x02 }]
+    }
+}
+
+/// A move distance or position carried by `MOT_MOVE_RELATIVE`, `MOT_MOVE_ABSOLUTE`,
+/// `MOT_SET_MOVERELPARAMS`, and `MOT_SET_MOVEABSPARAMS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    /// The channel ident.
+    pub channel: u16,
+    /// The relative distance or absolute position in encoder counts.
+    pub distance: i32,
+}
+
+impl Move {
+    /// Length of the data packet in bytes.
+    pub(super) const LENGTH: usize = 6;
+
+    pub(super) fn from_bytes(data: &[u8]) -> Self {
+        Self {
+            channel: word(&data[0..2]),
+            distance: long(&data[2..6]) as i32,
+        }
+    }
+
+    pub(super) fn to_bytes(self) -> Vec<u8> {
+        [
+            self.channel.to_le_bytes().as_slice(),
+            &self.distance.to_le_bytes(),
+        ]
+        .concat()
+    }
+}
+
+/// The status bits carried by `MOT_GET_STATUSBITS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusBits {
+    /// The channel ident.
+    pub channel: u16,
+    /// The status bits (Thorlabs APT Protocol, Issue 39, Page 126).
+    pub status_bits: u32,
+}
+
+impl StatusBits {
+    /// Length of the data packet in bytes.
+    pub(super) const LENGTH: usize = 6;
+
+    /// Decodes a complete `MOT_GET_STATUSBITS` message.
+    pub fn decode(message: &[u8]) -> Result<Self, Error> {
+        match decode(message)? {
+            Message::MotGetStatusBits(status) => Ok(status),
+            other => Err(Error::UnexpectedMessage(other.id())),
+        }
+    }
+
+    pub(super) fn from_bytes(data: &[u8]) -> Self {
+        Self {
+            channel: word(&data[0..2]),
+            status_bits: long(&data[2..6]),
+        }
+    }
+
+    pub(super) fn to_bytes(self) -> Vec<u8> {
+        [
+            self.channel.to_le_bytes().as_slice(),
+            &self.status_bits.to_le_bytes(),
+        ]
+        .concat()
+    }
+}
+
+/// The motor status carried by `MOT_GET_USTATUSUPDATE`, `MOT_MOVE_COMPLETED`, and
+/// `MOT_MOVE_STOPPED`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UStatus {
+    /// The channel ident.
+    pub channel: u16,
+    /// The position in encoder counts.
+    pub position: i32,
+    /// The velocity in device units.
+    pub velocity: u16,
+    /// The motor current in milliamps.
+    pub current: i16,
+    /// The status bits (Thorlabs APT Protocol, Issue 39, Page 126).
+    pub status_bits: u32,
+}
+
+impl UStatus {
+    /// Length of the data packet in bytes.
+    pub(super) const LENGTH: usize = 14;
+
+    /// Decodes a complete `MOT_GET_USTATUSUPDATE`, `MOT_MOVE_COMPLETED`, or `MOT_MOVE_STOPPED`
+    /// message.
+    pub fn decode(message: &[u8]) -> Result<Self, Error> {
+        match decode(message)? {
+            Message::MotGetUStatusUpdate(status)
+            | Message::MotMoveCompleted(status)
+            | Message::MotMoveStopped(status) => Ok(status),
+            other => Err(Error::UnexpectedMessage(other.id())),
+        }
+    }
+
+    pub(super) fn from_bytes(data: &[u8]) -> Self {
+        Self {
+            channel: word(&data[0..2]),
+            position: long(&data[2..6]) as i32,
+            velocity: word(&data[6..8]),
+            current: word(&data[8..10]) as i16,
+            status_bits: long(&data[10..14]),
+        }
+    }
+
+    pub(super) fn to_bytes(self) -> Vec<u8> {
+        [
+            self.channel.to_le_bytes().as_slice(),
+            &self.position.to_le_bytes(),
+            &self.velocity.to_le_bytes(),
+            &self.current.to_le_bytes(),
+            &self.status_bits.to_le_bytes(),
+        ]
+        .concat()
+    }
+}
+
+/// The trapezoidal velocity profile carried by `MOT_SET_VELPARAMS` and `MOT_GET_VELPARAMS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VelParams {
+    /// The channel ident.
+    pub channel: u16,
+    /// The minimum (start) velocity in device units. Currently unused by Thorlabs devices.
+    pub min_velocity: i32,
+    /// The acceleration in device units.
+    pub acceleration: i32,
+    /// The maximum velocity in device units.
+    pub max_velocity: i32,
+}
+
+impl VelParams {
+    /// Length of the data packet in bytes.
+    pub(super) const LENGTH: usize = 14;
+
+    /// Decodes a complete `MOT_GET_VELPARAMS` message.
+    pub fn decode(message: &[u8]) -> Result<Self, Error> {
+        match decode(message)? {
+            Message::MotGetVelParams(params) => Ok(params),
+            other => Err(Error::UnexpectedMessage(other.id())),
+        }
+    }
+
+    pub(super) fn from_bytes(data: &[u8]) -> Self {
+        Self {
+            channel: word(&data[0..2]),
+            min_velocity: long(&data[2..6]) as i32,
+            acceleration: long(&data[6..10]) as i32,
+            max_velocity: long(&data[10..14]) as i32,
+        }
+    }
+
+    pub(super) fn to_bytes(self) -> Vec<u8> {
+        [
+            self.channel.to_le_bytes().as_slice(),
+            &self.min_velocity.to_le_bytes(),
+            &self.acceleration.to_le_bytes(),
+            &self.max_velocity.to_le_bytes(),
+        ]
+        .concat()
+    }
+}
+
+/// The jog parameters carried by `MOT_SET_JOGPARAMS` and `MOT_GET_JOGPARAMS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JogParams {
+    /// The channel ident.
+    pub channel: u16,
+    /// Whether jog moves are continuous or single steps.
+    pub mode: JogMode,
+    /// The step size in encoder counts.
+    pub step_size: i32,
+    /// The minimum (start) velocity in device units. Currently unused by Thorlabs devices.
+    pub min_velocity: i32,
+    /// The acceleration in device units.
+    pub acceleration: i32,
+    /// The maximum velocity in device units.
+    pub max_velocity: i32,
+    /// How a continuous jog move stops.
+    pub stop_mode: StopMode,
+}
+
+impl JogParams {
+    /// Length of the data packet in bytes.
+    pub(super) const LENGTH: usize = 22;
+
+    /// Decodes a complete `MOT_GET_JOGPARAMS` message.
+    pub fn decode(message: &[u8]) -> Result<Self, Error> {
+        match decode(message)? {
+            Message::MotGetJogParams(params) => Ok(params),
+            other => Err(Error::UnexpectedMessage(other.id())),
+        }
+    }
+
+    pub(super) fn from_bytes(id: [u8; 2], data: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            channel: word(&data[0..2]),
+            mode: JogMode::from_param(id, data[2])?,
+            step_size: long(&data[4..8]) as i32,
+            min_velocity: long(&data[8..12]) as i32,
+            acceleration: long(&data[12..16]) as i32,
+            max_velocity: long(&data[16..20]) as i32,
+            stop_mode: StopMode::from_param(id, data[20])?,
+        })
+    }
+
+    pub(super) fn to_bytes(self) -> Vec<u8> {
+        [
+            self.channel.to_le_bytes().as_slice(),
+            &(self.mode.param() as u16).to_le_bytes(),
+            &self.step_size.to_le_bytes(),
+            &self.min_velocity.to_le_bytes(),
+            &self.acceleration.to_le_bytes(),
+            &self.max_velocity.to_le_bytes(),
+            &(self.stop_mode.param() as u16).to_le_bytes(),
+        ]
+        .concat()
+    }
+}
+
+/// The homing parameters carried by `MOT_SET_HOMEPARAMS` and `MOT_GET_HOMEPARAMS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HomeParams {
+    /// The channel ident.
+    pub channel: u16,
+    /// The direction of the homing move.
+    pub direction: Direction,
+    /// The limit switch which defines the home position.
+    pub limit_switch: LimitSwitch,
+    /// The homing velocity in device units.
+    pub velocity: i32,
+    /// The distance of the home position from the limit switch in encoder counts.
+    pub offset: i32,
+}
+
+impl HomeParams {
+    /// Length of the data packet in bytes.
+    pub(super) const LENGTH: usize = 14;
+
+    /// Decodes a complete `MOT_GET_HOMEPARAMS` message.
+    pub fn decode(message: &[u8]) -> Result<Self, Error> {
+        match decode(message)? {
+            Message::MotGetHomeParams(params) => Ok(params),
+            other => Err(Error::UnexpectedMessage(other.id())),
+        }
+    }
+
+    pub(super) fn from_bytes(id: [u8; 2], data: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            channel: word(&data[0..2]),
+            direction: Direction::from_param(id, data[2])?,
+            limit_switch: LimitSwitch::from_param(id, data[4])?,
+            velocity: long(&data[6..10]) as i32,
+            offset: long(&data[10..14]) as i32,
+        })
+    }
+
+    pub(super) fn to_bytes(self) -> Vec<u8> {
+        [
+            self.channel.to_le_bytes().as_slice(),
+            &(self.direction.param() as u16).to_le_bytes(),
+            &(self.limit_switch.param() as u16).to_le_bytes(),
+            &self.velocity.to_le_bytes(),
+            &self.offset.to_le_bytes(),
+        ]
+        .concat()
+    }
+}
+
+/// The limit switch parameters carried by `MOT_SET_LIMSWITCHPARAMS` and
+/// `MOT_GET_LIMSWITCHPARAMS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimSwitchParams {
+    /// The channel ident.
+    pub channel: u16,
+    /// The operation of the forward hardware limit switch.
+    pub cw_hard_limit: HardLimit,
+    /// The operation of the reverse hardware limit switch.
+    pub ccw_hard_limit: HardLimit,
+    /// `True` if the forward and reverse hardware limit switches are swapped.
+    pub swapped: bool,
+    /// The forward software limit in encoder counts.
+    pub cw_soft_limit: i32,
+    /// The reverse software limit in encoder counts.
+    pub ccw_soft_limit: i32,
+    /// The action taken when a software limit is reached.
+    pub soft_limit_mode: SoftLimitMode,
+    /// `True` if the software limits are applied as for a rotation stage.
+    pub rotation: bool,
+}
+
+impl LimSwitchParams {
+    /// Length of the data packet in bytes.
+    pub(super) const LENGTH: usize = 16;
+    /// Bit set in the forward hardware limit word if the limit switches are swapped.
+    const SWAPPED: u8 = 0x80;
+    /// Bit set in the software limit mode word if the stage is a rotation stage.
+    const ROTATION: u8 = 0x80;
+
+    /// Decodes a complete `MOT_GET_LIMSWITCHPARAMS` message.
+    pub fn decode(message: &[u8]) -> Result<Self, Error> {
+        match decode(message)? {
+            Message::MotGetLimSwitchParams(params) => Ok(params),
+            other => Err(Error::UnexpectedMessage(other.id())),
+        }
+    }
+
+    pub(super) fn from_bytes(id: [u8; 2], data: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            channel: word(&data[0..2]),
+            cw_hard_limit: HardLimit::from_param(id, data[2] & !Self::SWAPPED)?,
+            ccw_hard_limit: HardLimit::from_param(id, data[4])?,
+            swapped: data[2] & Self::SWAPPED != 0,
+            cw_soft_limit: long(&data[6..10]) as i32,
+            ccw_soft_limit: long(&data[10..14]) as i32,
+            soft_limit_mode: SoftLimitMode::from_param(id, data[14] & !Self::ROTATION)?,
+            rotation: data[14] & Self::ROTATION != 0,
+        })
+    }
+
+    pub(super) fn to_bytes(self) -> Vec<u8> {
+        let swapped = if self.swapped { Self::SWAPPED } else { 0x00 };
+        let rotation = if self.rotation { Self::ROTATION } else { 0x00 };
+        [
+            self.channel.to_le_bytes().as_slice(),
+            &((self.cw_hard_limit.param() | swapped) as u16).to_le_bytes(),
+            &(self.ccw_hard_limit.param() as u16).to_le_bytes(),
+            &self.cw_soft_limit.to_le_bytes(),
+            &self.ccw_soft_limit.to_le_bytes(),
+            &((self.soft_limit_mode.param() | rotation) as u16).to_le_bytes(),
+        ]
+        .concat()
+    }
+}
+
+/// The error report carried by `HW_RICHRESPONSE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RichResponse {
+    /// The ID of the message which caused the error.
+    pub message_id: [u8; 2],
+    /// The numerical error code.
+    pub code: u16,
+    /// A description of the error. At most 64 bytes are transmitted.
+    pub notes: String,
+}
+
+impl RichResponse {
+    /// Length of the data packet in bytes.
+    pub(super) const LENGTH: usize = 68;
+
+    pub(super) fn from_bytes(data: &[u8]) -> Self {
+        let notes = data[4..].split(|&b| b == 0).next().unwrap_or_default();
+        Self {
+            message_id: [data[0], data[1]],
+            code: word(&data[2..4]),
+            notes: String::from_utf8_lossy(notes).to_string(),
+        }
+    }
+
+    pub(super) fn to_bytes(&self) -> Vec<u8> {
+        let mut data = [
+            self.message_id.as_slice(),
+            &self.code.to_le_bytes(),
+            self.notes.as_bytes(),
+        ]
+        .concat();
+        data.resize(Self::LENGTH, 0x00); // Notes are truncated or zero-padded to 64 bytes
+        data
+    }
+}
+
+/// The stop mode carried by `MOT_MOVE_STOP`, `MOT_SET_JOGPARAMS`, and `MOT_GET_JOGPARAMS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopMode {
+    /// Stop immediately (emergency stop).
+    Immediate,
+    /// Stop using the current velocity profile.
+    Profiled,
+}
+
+impl StopMode {
+    pub(super) fn from_param(id: [u8; 2], value: u8) -> Result<Self, Error> {
+        match value {
+            0x01 => Ok(Self::Immediate),
+            0x02 => Ok(Self::Profiled),
+            value => Err(Error::InvalidValue { id, value }),
+        }
+    }
+
+    pub(super) fn param(self) -> u8 {
+        match self {
+            Self::Immediate => 0x01,
+            Self::Profiled => 0x02,
+        }
+    }
+}
+
+/// The jog mode carried by `MOT_SET_JOGPARAMS` and `MOT_GET_JOGPARAMS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JogMode {
+    /// Move continuously until stopped, or until a limit switch is reached.
+    Continuous,
+    /// Move by a single step.
+    Step,
+}
+
+impl JogMode {
+    pub(super) fn from_param(id: [u8; 2], value: u8) -> Result<Self, Error> {
+        match value {
+            0x01 => Ok(Self::Continuous),
+            0x02 => Ok(Self::Step),
+            value => Err(Error::InvalidValue { id, value }),
+        }
+    }
+
+    pub(super) fn param(self) -> u8 {
+        match self {
+            Self::Continuous => 0x01,
+            Self::Step => 0x02,
+        }
+    }
+}
+
+/// The direction carried by `MOT_MOVE_JOG`, `MOT_SET_HOMEPARAMS`, and `MOT_GET_HOMEPARAMS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Move towards increasing position.
+    Forward,
+    /// Move towards decreasing position.
+    Reverse,
+}
+
+impl Direction {
+    pub(super) fn from_param(id: [u8; 2], value: u8) -> Result<Self, Error> {
+        match value {
+            0x01 => Ok(Self::Forward),
+            0x02 => Ok(Self::Reverse),
+            value => Err(Error::InvalidValue { id, value }),
+        }
+    }
+
+    pub(super) fn param(self) -> u8 {
+        match self {
+            Self::Forward => 0x01,
+            Self::Reverse => 0x02,
+        }
+    }
+}
+
+/// The home limit switch carried by `MOT_SET_HOMEPARAMS` and `MOT_GET_HOMEPARAMS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitSwitch {
+    /// The reverse hardware limit switch.
+    Reverse,
+    /// The forward hardware limit switch.
+    Forward,
+}
+
+impl LimitSwitch {
+    pub(super) fn from_param(id: [u8; 2], value: u8) -> Result<Self, Error> {
+        match value {
+            0x01 => Ok(Self::Reverse),
+            0x04 => Ok(Self::Forward),
+            value => Err(Error::InvalidValue { id, value }),
+        }
+    }
+
+    pub(super) fn param(self) -> u8 {
+        match self {
+            Self::Reverse => 0x01,
+            Self::Forward => 0x04,
+        }
+    }
+}
+
+/// The hardware limit switch operation carried by `MOT_SET_LIMSWITCHPARAMS` and
+/// `MOT_GET_LIMSWITCHPARAMS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HardLimit {
+    /// The limit switch is ignored or absent.
+    Ignore,
+    /// The limit switch makes on contact.
+    Makes,
+    /// The limit switch breaks on contact.
+    Breaks,
+    /// The limit switch makes on contact, and is only used for homing.
+    MakesHomeOnly,
+    /// The limit switch breaks on contact, and is only used for homing.
+    BreaksHomeOnly,
+    /// The encoder index mark is used for homing.
+    IndexMark,
+}
+
+impl HardLimit {
+    pub(super) fn from_param(id: [u8; 2], value: u8) -> Result<Self, Error> {
+        match value {
+            0x01 => Ok(Self::Ignore),
+            0x02 => Ok(Self::Makes),
+            0x03 => Ok(Self::Breaks),
+            0x04 => Ok(Self::MakesHomeOnly),
+            0x05 => Ok(Self::BreaksHomeOnly),
+            0x06 => Ok(Self::IndexMark),
+            value => Err(Error::InvalidValue { id, value }),
+        }
+    }
+
+    pub(super) fn param(self) -> u8 {
+        match self {
+            Self::Ignore => 0x01,
+            Self::Makes => 0x02,
+            Self::Breaks => 0x03,
+            Self::MakesHomeOnly => 0x04,
+            Self::BreaksHomeOnly => 0x05,
+            Self::IndexMark => 0x06,
+        }
+    }
+}
+
+/// The software limit mode carried by `MOT_SET_LIMSWITCHPARAMS` and `MOT_GET_LIMSWITCHPARAMS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoftLimitMode {
+    /// The software limits are ignored.
+    Ignore,
+    /// Stop immediately when a software limit is reached.
+    Immediate,
+    /// Stop using the current velocity profile when a software limit is reached.
+    Profiled,
+}
+
+impl SoftLimitMode {
+    pub(super) fn from_param(id: [u8; 2], value: u8) -> Result<Self, Error> {
+        match value {
+            0x01 => Ok(Self::Ignore),
+            0x02 => Ok(Self::Immediate),
+            0x03 => Ok(Self::Profiled),
+            value => Err(Error::InvalidValue { id, value }),
+        }
+    }
+
+    pub(super) fn param(self) -> u8 {
+        match self {
+            Self::Ignore => 0x01,
+            Self::Immediate => 0x02,
+            Self::Profiled => 0x03,
+        }
+    }
+}
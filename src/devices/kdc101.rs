@@ -9,14 +9,26 @@ modification, are permitted provided that the conditions of the LICENSE are met.
 */
 
 use std::fmt::{Display, Formatter};
-use std::io::Error;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
-use crate::devices::{UsbPrimitive, add_device};
-use crate::error::sn;
+use smol::stream::Stream;
+
+use crate::apt::{Addressing, Direction, id};
+use crate::devices::{UsbPrimitive, add_device, get_device};
+use crate::error::{DeviceFault, Error, sn};
 use crate::functions;
-use crate::messages::Metadata;
-use crate::traits::{CheckSerialNumber, ThorlabsDevice, UnitConversion, Units};
+use crate::messages::{
+    Event,
+    HomeSettings,
+    JogSettings,
+    LimitSwitchSettings,
+    Metadata,
+    StatusUpdate,
+};
+use crate::traits::{CheckSerialNumber, ThorlabsDevice, Transport, UnitConversion, Units};
+use crate::transports::{Serial, Simulator, Usb};
 
 /// KDC101 devices have one channel.
 const CH: usize = 1;
@@ -27,52 +39,181 @@ pub struct KDC101 {
     inner: Arc<UsbPrimitive<CH>>,
 }
 
-impl KDC101 {}
+impl KDC101 {
+    /// Constructs a new [`KDC101`] which communicates with the device through the provided
+    /// [`Transport`].
+    ///
+    /// To connect to a USB device, see [`new`][1].
+    ///
+    /// [1]: KDC101::new
+    pub fn with_transport<A, T>(serial_number: A, transport: T) -> Result<Self, Error>
+    where
+        A: Into<String>,
+        T: Transport,
+    {
+        Self::with_responses(serial_number, transport, &[])
+    }
+
+    /// Constructs a new [`KDC101`] which communicates with the device through the provided
+    /// [`Transport`], and registers additional response IDs for use with [`request_raw`][1].
+    ///
+    /// Responses which Thormotion already registers are ignored.
+    ///
+    /// [1]: ThorlabsDevice::request_raw
+    pub fn with_responses<A, T>(
+        serial_number: A,
+        transport: T,
+        responses: &[Metadata<CH>],
+    ) -> Result<Self, Error>
+    where
+        A: Into<String>,
+        T: Transport,
+    {
+        Self::build(serial_number, transport, responses, Addressing::Generic)
+    }
+
+    /// Constructs a new [`KDC101`] which communicates with the device through the provided
+    /// [`Transport`], and addresses the device as described by [`Addressing`].
+    ///
+    /// Use [`Addressing::Bays`] if the device is a bay in a card slot system. Otherwise, see
+    /// [`with_transport`][1].
+    ///
+    /// [1]: KDC101::with_transport
+    pub fn with_addressing<A, T>(
+        serial_number: A,
+        transport: T,
+        addressing: Addressing,
+    ) -> Result<Self, Error>
+    where
+        A: Into<String>,
+        T: Transport,
+    {
+        Self::build(serial_number, transport, &[], addressing)
+    }
+
+    /// Constructs a new [`KDC101`] and adds it to the global [`DEVICES`][1] registry.
+    ///
+    /// [1]: crate::devices::utils::DEVICES
+    fn build<A, T>(
+        serial_number: A,
+        transport: T,
+        responses: &[Metadata<CH>],
+        addressing: Addressing,
+    ) -> Result<Self, Error>
+    where
+        A: Into<String>,
+        T: Transport,
+    {
+        let sn = serial_number.into();
+        Self::check_serial_number(&sn)?;
+        let ids = [Self::IDS.as_slice(), responses].concat();
+        let device = Self {
+            inner: Arc::new(UsbPrimitive::new(&sn, &ids, addressing, Box::new(transport))),
+        };
+        let d = device.clone(); // Inexpensive Arc Clone
+        let f = move || d.abort();
+        add_device(sn, f);
+        Ok(device)
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/faults.md")]
+    pub async fn faults_async(&self) -> impl Stream<Item = DeviceFault> + Send + Unpin + use<> {
+        self.inner.faults().await
+    }
+
+    #[doc = include_str!("../documentation/events.md")]
+    pub async fn events_async(&self) -> impl Stream<Item = Event> + Send + Unpin + use<> {
+        self.inner.events().await
+    }
+
+    #[doc = include_str!("../documentation/events.md")]
+    pub fn events(&self) -> impl Iterator<Item = Event> + Send + use<> {
+        // The sync function blocks the current thread while waiting for each event.
+        smol::stream::block_on(smol::block_on(self.events_async()))
+    }
+
+    #[doc = include_str!("../documentation/status_stream.md")]
+    pub async fn status_stream_async(
+        &self,
+    ) -> impl Stream<Item = StatusUpdate> + Send + Unpin + use<> {
+        functions::status_stream(self).await
+    }
+
+    #[doc = include_str!("../documentation/status_stream.md")]
+    pub fn status_stream(&self) -> impl Iterator<Item = StatusUpdate> + Send + use<> {
+        // The sync function blocks the current thread while waiting for each update.
+        smol::stream::block_on(smol::block_on(self.status_stream_async()))
+    }
+
+    /// Constructs a new [`KDC101`] from a serial number or the path to a tty device. See
+    /// [`new`][1].
+    ///
+    /// [1]: KDC101::new
+    fn connect(target: String) -> Result<Self, Error> {
+        if target.starts_with('/') {
+            // The device is owned by a kernel serial driver
+            let serial = Serial::new(&target);
+            return Self::with_transport(serial.serial_number()?, serial);
+        }
+        Self::check_serial_number(&target)?;
+        match get_device(&target) {
+            Ok(device) => Self::with_transport(target, Usb::new(device)),
+            // The device may only be reachable through a kernel serial driver
+            Err(Error::SerialNumber(sn::Error::NotFound(sn))) => match Serial::find(&sn) {
+                Ok(serial) => Self::with_transport(sn, serial),
+                Err(_) => Err(sn::Error::NotFound(sn).into()),
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+        /// Constructs a new [`KDC101`] connected to an in-process [`Simulator`].
+    ///
+    /// The simulated device answers every command in the same way as a real device, which allows
+    /// automation code to be tested without hardware.
+    pub fn simulated<A>(serial_number: A) -> Result<Self, Error>
+    where
+        A: Into<String>,
+    {
+        Self::with_transport(serial_number, Simulator::new::<Self>())
+    }
+}
 
 #[cfg_attr(feature = "py", pyo3::pymethods)]
 impl KDC101 {
-    const IDS: [Metadata<1>; 6] = [
+    const IDS: [Metadata<1>; 10] = [
         // MOD
-        Metadata::header([0x12, 0x02]), // GET_CHANENABLESTATE
+        Metadata::header(id::MOD_GET_CHANENABLESTATE),
         // STATUS
-        Metadata::payload([0x91, 0x04], 20), // GET_U_STATUS_UPDATE
-        Metadata::payload([0x2A, 0x04], 12), // GET_STATUS_BITS
+        Metadata::payload(id::MOT_GET_USTATUSUPDATE, 20),
+        Metadata::payload(id::MOT_GET_STATUSBITS, 12),
+        // PARAMETERS
+        Metadata::payload(id::MOT_GET_VELPARAMS, 20),
+        Metadata::payload(id::MOT_GET_JOGPARAMS, 28),
+        Metadata::payload(id::MOT_GET_HOMEPARAMS, 20),
+        Metadata::payload(id::MOT_GET_LIMSWITCHPARAMS, 22),
         // MOVE
-        Metadata::header([0x44, 0x04]), // MOVE_HOMED
-        Metadata::payload([0x64, 0x04], 20), // MOVE_COMPLETED
+        Metadata::header(id::MOT_MOVE_HOMED),
+        Metadata::payload(id::MOT_MOVE_COMPLETED, 20),
         // STOP
-        Metadata::payload([0x66, 0x04], 20), // MOVE_STOPPED
+        Metadata::payload(id::MOT_MOVE_STOPPED, 20),
     ];
 
     #[cfg(not(feature = "py"))]
     #[doc = include_str!("../documentation/new.md")]
-    pub fn new<A>(serial_number: A) -> Result<Self, sn::Error>
+    pub fn new<A>(serial_number: A) -> Result<Self, Error>
     where
         A: Into<String>,
     {
-        let sn = serial_number.into();
-        Self::check_serial_number(&sn)?;
-        let device = Self {
-            inner: Arc::new(UsbPrimitive::new(&sn, &Self::IDS)?),
-        };
-        let d = device.clone(); // Inexpensive Arc Clone
-        let f = move || d.abort();
-        add_device(sn, f);
-        Ok(device)
+        Self::connect(serial_number.into())
     }
 
     #[cfg(feature = "py")]
     #[new]
     #[doc = include_str!("../documentation/new.md")]
-    pub fn new(serial_number: String) -> Result<Self, sn::Error> {
-        Self::check_serial_number(&sn)?;
-        let device = Self {
-            inner: Arc::new(UsbPrimitive::new(&sn, &Self::IDS)?),
-        };
-        let d = device.clone(); // Inexpensive Arc Clone
-        let f = move || d.abort();
-        add_device(sn, f);
-        Ok(device)
+    pub fn new(serial_number: String) -> Result<Self, Error> {
+        Self::connect(serial_number)
     }
 
     /* ------------------------------------------------------------------------------------- MOD */
@@ -80,13 +221,13 @@ impl KDC101 {
     #[thormacros::sync]
     #[doc = include_str!("../documentation/open.md")]
     pub async fn open_async(&mut self) -> Result<(), Error> {
-        self.inner.open().await
+        Ok(self.inner.open().await?)
     }
 
     #[thormacros::sync]
     #[doc = include_str!("../documentation/close.md")]
     pub async fn close_async(&mut self) -> Result<(), Error> {
-        self.inner.close().await
+        Ok(self.inner.close().await?)
     }
 
     #[thormacros::sync]
@@ -95,137 +236,353 @@ impl KDC101 {
         self.inner.is_open().await
     }
 
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/timeout.md")]
+    pub async fn timeout_async(&self) -> Option<Duration> {
+        self.inner.timeout().await
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/set_timeout.md")]
+    pub async fn set_timeout_async(&self, timeout: Option<Duration>) {
+        self.inner.set_timeout(timeout).await
+    }
+
     #[thormacros::sync]
     #[doc = include_str!("../documentation/is_channel_enabled.md")]
-    pub async fn is_channel_enabled_async(&self) -> bool {
+    pub async fn is_channel_enabled_async(&self) -> Result<bool, Error> {
         functions::is_channel_enabled(self, 1).await
     }
 
     #[thormacros::sync]
     #[doc = include_str!("../documentation/set_channel_enable_state.md")]
-    pub async fn set_channel_enabled_async(&self, enable: bool) {
+    pub async fn set_channel_enabled_async(&self, enable: bool) -> Result<(), Error> {
         functions::set_channel_enabled(self, 1, enable).await
     }
 
     #[thormacros::sync]
     #[doc = include_str!("../documentation/identify.md")]
-    pub async fn identify_async(&self) {
-        functions::identify(self, 1).await;
+    pub async fn identify_async(&self) -> Result<(), Error> {
+        functions::identify(self, 1).await
     }
 
     /* ---------------------------------------------------------------------------------- STATUS */
 
     #[thormacros::sync]
     #[doc = include_str!("../documentation/start_update_messages.md")]
-    pub async fn start_update_messages_async(&self) {
+    pub async fn start_update_messages_async(&self) -> Result<(), Error> {
         functions::start_update_messages(self).await
     }
 
     #[thormacros::sync]
     #[doc = include_str!("../documentation/stop_update_messages.md")]
-    pub async fn stop_update_messages_async(&self) {
+    pub async fn stop_update_messages_async(&self) -> Result<(), Error> {
         functions::stop_update_messages(self).await
     }
 
     #[thormacros::sync]
     #[doc = include_str!("../documentation/get_status.md")]
-    pub async fn get_status_async(&self) -> (f64, f64, u32) {
+    pub async fn get_status_async(&self) -> Result<(f64, f64, u32), Error> {
         functions::get_u_status_update(self, 1).await
     }
 
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/get_status.md")]
+    #[doc = include_str!("../documentation/max_age.md")]
+    pub async fn get_status_with_max_age_async(
+        &self,
+        max_age: Duration,
+    ) -> Result<(f64, f64, u32), Error> {
+        functions::get_u_status_update_cached(self, 1, max_age).await
+    }
+
     #[thormacros::sync]
     #[doc = include_str!("../documentation/get_position.md")]
-    pub async fn get_position_async(&self) -> f64 {
-        self.get_status_async().await.0
+    pub async fn get_position_async(&self) -> Result<f64, Error> {
+        Ok(self.get_status_async().await?.0)
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/get_position.md")]
+    #[doc = include_str!("../documentation/max_age.md")]
+    pub async fn get_position_with_max_age_async(&self, max_age: Duration) -> Result<f64, Error> {
+        Ok(self.get_status_with_max_age_async(max_age).await?.0)
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/get_velocity.md")]
+    pub async fn get_velocity_async(&self) -> Result<f64, Error> {
+        Ok(self.get_status_async().await?.1)
     }
 
     #[thormacros::sync]
     #[doc = include_str!("../documentation/get_velocity.md")]
-    pub async fn get_velocity_async(&self) -> f64 {
-        self.get_status_async().await.1
+    #[doc = include_str!("../documentation/max_age.md")]
+    pub async fn get_velocity_with_max_age_async(&self, max_age: Duration) -> Result<f64, Error> {
+        Ok(self.get_status_with_max_age_async(max_age).await?.1)
     }
 
     #[thormacros::sync]
     #[doc = include_str!("../documentation/get_status_bits.md")]
-    pub async fn get_status_bits_async(&self) -> u32 {
+    pub async fn get_status_bits_async(&self) -> Result<u32, Error> {
         functions::get_status_bits(self, 1).await
     }
 
     #[thormacros::sync]
-    pub async fn in_motion_cw_async(&self) -> bool {
-        let bits = self.get_status_bits_async().await;
-        (bits & 0x00000010) != 0
+    #[doc = include_str!("../documentation/get_status_bits.md")]
+    #[doc = include_str!("../documentation/max_age.md")]
+    pub async fn get_status_bits_with_max_age_async(
+        &self,
+        max_age: Duration,
+    ) -> Result<u32, Error> {
+        functions::get_status_bits_cached(self, 1, max_age).await
+    }
+
+    #[thormacros::sync]
+    pub async fn in_motion_cw_async(&self) -> Result<bool, Error> {
+        self.in_motion_cw_with_max_age_async(Duration::ZERO).await
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/max_age.md")]
+    pub async fn in_motion_cw_with_max_age_async(&self, max_age: Duration) -> Result<bool, Error> {
+        let bits = self.get_status_bits_with_max_age_async(max_age).await?;
+        Ok((bits & 0x00000010) != 0)
     }
 
     #[thormacros::sync]
-    pub async fn in_motion_ccw_async(&self) -> bool {
-        let bits = self.get_status_bits_async().await;
-        (bits & 0x00000020) != 0
+    pub async fn in_motion_ccw_async(&self) -> Result<bool, Error> {
+        self.in_motion_ccw_with_max_age_async(Duration::ZERO).await
     }
 
     #[thormacros::sync]
-    pub async fn in_motion_async(&self) -> bool {
-        let bits = self.get_status_bits_async().await;
-        (bits & 0x00000030) != 0
+    #[doc = include_str!("../documentation/max_age.md")]
+    pub async fn in_motion_ccw_with_max_age_async(&self, max_age: Duration) -> Result<bool, Error> {
+        let bits = self.get_status_bits_with_max_age_async(max_age).await?;
+        Ok((bits & 0x00000020) != 0)
+    }
+
+    #[thormacros::sync]
+    pub async fn in_motion_async(&self) -> Result<bool, Error> {
+        self.in_motion_with_max_age_async(Duration::ZERO).await
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/max_age.md")]
+    pub async fn in_motion_with_max_age_async(&self, max_age: Duration) -> Result<bool, Error> {
+        let bits = self.get_status_bits_with_max_age_async(max_age).await?;
+        Ok((bits & 0x00000030) != 0)
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/is_homed.md")]
+    pub async fn is_homed_async(&self) -> Result<bool, Error> {
+        self.is_homed_with_max_age_async(Duration::ZERO).await
     }
 
     #[thormacros::sync]
     #[doc = include_str!("../documentation/is_homed.md")]
-    pub async fn is_homed_async(&self) -> bool {
-        let bits = self.get_status_bits_async().await;
-        (bits & 0x00000400) != 0
+    #[doc = include_str!("../documentation/max_age.md")]
+    pub async fn is_homed_with_max_age_async(&self, max_age: Duration) -> Result<bool, Error> {
+        let bits = self.get_status_bits_with_max_age_async(max_age).await?;
+        Ok((bits & 0x00000400) != 0)
+    }
+
+    /* ------------------------------------------------------------------------------ PARAMETERS */
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/get_velocity_params.md")]
+    pub async fn get_velocity_params_async(&self) -> Result<(f64, f64), Error> {
+        functions::get_velocity_params(self, 1).await
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/set_velocity_params.md")]
+    pub async fn set_velocity_params_async(
+        &self,
+        max_velocity: f64,
+        acceleration: f64,
+    ) -> Result<(), Error> {
+        functions::set_velocity_params(self, 1, max_velocity, acceleration).await
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/get_jog_params.md")]
+    pub async fn get_jog_params_async(&self) -> Result<JogSettings, Error> {
+        functions::get_jog_params(self, 1).await
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/set_jog_params.md")]
+    pub async fn set_jog_params_async(&self, settings: JogSettings) -> Result<(), Error> {
+        functions::set_jog_params(self, 1, settings).await
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/get_home_params.md")]
+    pub async fn get_home_params_async(&self) -> Result<HomeSettings, Error> {
+        functions::get_home_params(self, 1).await
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/set_home_params.md")]
+    pub async fn set_home_params_async(&self, settings: HomeSettings) -> Result<(), Error> {
+        functions::set_home_params(self, 1, settings).await
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/get_limit_switch_params.md")]
+    pub async fn get_limit_switch_params_async(&self) -> Result<LimitSwitchSettings, Error> {
+        functions::get_limit_switch_params(self, 1).await
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/set_limit_switch_params.md")]
+    pub async fn set_limit_switch_params_async(
+        &self,
+        settings: LimitSwitchSettings,
+    ) -> Result<(), Error> {
+        functions::set_limit_switch_params(self, 1, settings).await
     }
 
     /* ------------------------------------------------------------------------------------ MOVE */
 
     #[thormacros::sync]
     #[doc = include_str!("../documentation/home.md")]
-    pub async fn home_async(&self) {
-        functions::home(self, 1).await
+    pub async fn home_async(&self) -> Result<(), Error> {
+        functions::home(self, 1, self.inner.timeout().await).await
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/home.md")]
+    ///
+    /// Returns [`Timeout`][1] if homing does not complete within the specified `timeout`.
+    ///
+    /// [1]: crate::error::cmd::Error::Timeout
+    pub async fn home_with_timeout_async(&self, timeout: Duration) -> Result<(), Error> {
+        functions::home(self, 1, Some(timeout)).await
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/move_absolute.md")]
+    pub async fn move_absolute_async(&self, position: f64) -> Result<(), Error> {
+        functions::move_absolute(self, 1, position, self.inner.timeout().await).await
     }
 
     #[thormacros::sync]
     #[doc = include_str!("../documentation/move_absolute.md")]
-    pub async fn move_absolute_async(&self, position: f64) {
-        functions::move_absolute(self, 1, position).await
+    ///
+    /// Returns [`Timeout`][1] if the move does not complete within the specified `timeout`.
+    ///
+    /// [1]: crate::error::cmd::Error::Timeout
+    pub async fn move_absolute_with_timeout_async(
+        &self,
+        position: f64,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        functions::move_absolute(self, 1, position, Some(timeout)).await
     }
 
     #[thormacros::sync]
     #[doc = include_str!("../documentation/move_absolute_from_params.md")]
-    pub async fn move_absolute_from_params_async(&self) -> f64 {
+    pub async fn move_absolute_from_params_async(&self) -> Result<f64, Error> {
         functions::move_absolute_from_params(self, 1).await
     }
 
     #[thormacros::sync]
-    pub async fn move_relative_async(&self, distance: f64) {
-        let start = self.get_position_async().await;
-        functions::move_relative(self, 1, distance).await;
-        let end = self.get_position_async().await;
-        if !Units::approx((end - start).abs(), distance) {
-            log::error!("{self} MOVE_RELATIVE (failed tolerance) START {start:.3} END {end:.3}");
-        }
+    pub async fn move_relative_async(&self, distance: f64) -> Result<(), Error> {
+        let timeout = self.inner.timeout().await;
+        self.move_relative_timeout(distance, timeout).await
     }
 
     #[thormacros::sync]
-    pub async fn move_relative_from_params_async(&self) -> f64 {
+    #[doc = include_str!("../documentation/move_relative.md")]
+    ///
+    /// Returns [`Timeout`][1] if the move does not complete within the specified `timeout`.
+    ///
+    /// [1]: crate::error::cmd::Error::Timeout
+    pub async fn move_relative_with_timeout_async(
+        &self,
+        distance: f64,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        self.move_relative_timeout(distance, Some(timeout)).await
+    }
+
+    #[thormacros::sync]
+    pub async fn move_relative_from_params_async(&self) -> Result<f64, Error> {
         functions::move_relative_from_params(self, 1).await
     }
 
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/jog.md")]
+    pub async fn jog_async(&self, direction: Direction) -> Result<(), Error> {
+        functions::jog(self, 1, direction, self.inner.timeout().await).await
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/jog.md")]
+    ///
+    /// Returns [`Timeout`][5] if the move does not complete within the specified `timeout`.
+    ///
+    /// [5]: crate::error::cmd::Error::Timeout
+    pub async fn jog_with_timeout_async(
+        &self,
+        direction: Direction,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        functions::jog(self, 1, direction, Some(timeout)).await
+    }
+
+    /* ---------------------------------------------------------------------------------- RECORD */
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/record.md")]
+    pub async fn record_async(&self, path: PathBuf) -> Result<(), Error> {
+        Ok(self.inner.record(&path).await?)
+    }
+
+    #[thormacros::sync]
+    #[doc = include_str!("../documentation/stop_recording.md")]
+    pub async fn stop_recording_async(&self) {
+        self.inner.stop_recording().await
+    }
+
     /* ------------------------------------------------------------------------------------ STOP */
 
     #[thormacros::sync]
     #[doc = include_str!("../documentation/stop.md")]
-    pub async fn stop_async(&self) {
+    pub async fn stop_async(&self) -> Result<(), Error> {
         functions::stop(self, 1).await
     }
 
     #[thormacros::sync]
     #[doc = include_str!("../documentation/estop.md")]
-    pub async fn estop_async(&self) {
-        match self.in_motion_async().await {
+    pub async fn estop_async(&self) -> Result<(), Error> {
+        match self.in_motion_async().await? {
             true => functions::estop(self, 1).await,
-            false => log::info!("{self} ESTOP (not in motion)"),
+            false => {
+                log::info!("{self} ESTOP (not in motion)");
+                Ok(())
+            }
+        }
+    }
+}
+
+impl KDC101 {
+    /// Moves the device by a relative distance (mm), then checks the distance travelled.
+    async fn move_relative_timeout(
+        &self,
+        distance: f64,
+        timeout: Option<Duration>,
+    ) -> Result<(), Error> {
+        let start = self.get_position_async().await?;
+        functions::move_relative(self, 1, distance, timeout).await?;
+        let end = self.get_position_async().await?;
+        if !Units::approx((end - start).abs(), distance) {
+            log::error!("{self} MOVE_RELATIVE (failed tolerance) START {start:.3} END {end:.3}");
         }
+        Ok(())
     }
 }
 
@@ -236,7 +593,12 @@ impl ThorlabsDevice<CH> for KDC101 {
 
     fn abort(&self) {
         log::info!("{self} ABORT (requested)");
-        self.estop();
+        match self.is_open() {
+            true => self
+                .estop()
+                .unwrap_or_else(|e| log::error!("{self} ABORT (failed) {e}")),
+            false => log::info!("{self} ABORT (is closed)"),
+        }
         log::info!("{self} ABORT (success)");
     }
 }
@@ -17,7 +17,7 @@ mod utils;
 /* ------------------------------------------------------------------------------ Public Exports */
 
 pub use kdc101::KDC101;
-pub use utils::{get_devices, show_devices};
+pub use utils::{abort_on_error, get_devices, show_devices};
 
 /* ----------------------------------------------------------------------------- Private Exports */
 
@@ -10,150 +10,295 @@ modification, are permitted provided that the conditions of the LICENSE are met.
 
 use std::collections::VecDeque;
 use std::fmt::{Debug, Display, Formatter};
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
-use nusb::transfer::{Buffer, Bulk, In, Out, TransferError};
-use nusb::{Endpoint, Interface};
-use smol::Task;
 use smol::lock::Mutex;
+use smol::stream::StreamExt;
+use smol::{Task, Timer};
 
-use super::serial_port;
-use crate::devices::abort;
+use crate::apt::id::HW_DISCONNECT;
+use crate::apt::{self, Message, length};
+use crate::devices::bug_abort;
+use crate::messages::session::Direction;
 use crate::messages::{CMD_LEN_MAX, Dispatcher};
+use crate::traits::{Incoming, Transport};
 
-/// The USB endpoint used for incoming commands from the device
-const IN_ENDPOINT: u8 = 0x81;
-/// The number of concurrent transfers to maintain in the queue
-const N_TRANSFERS: usize = 3;
-/// The USB endpoint used for outgoing commands to the device
-const OUT_ENDPOINT: u8 = 0x02;
-/// Background task polling interval
-const TIME: Duration = Duration::from_millis(10);
+/// Interval between `MOT_ACK_USTATUSUPDATE` messages while update messages are enabled.
+///
+/// Devices connected by USB stop sending update messages if they are not acknowledged regularly.
+#[cfg(not(test))]
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(1);
+/// Interval between `MOT_ACK_USTATUSUPDATE` messages while update messages are enabled. Shortened
+/// in tests, so that keep-alive tests do not wait for whole seconds.
+#[cfg(test)]
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_millis(100);
 
-/// Handles all incoming and outgoing commands between the host and a specific USB [`Interface`].
+/// A [`Transport`] shared between the [`UsbPrimitive`][1] and its [`Communicator`]. Protected by
+/// a [`Mutex`] for async access.
+///
+/// [1]: crate::devices::UsbPrimitive
+pub(super) type SharedTransport = Arc<Mutex<Box<dyn Transport>>>;
+
+/// A callback which is invoked once if the [`incoming task`][1] terminates unexpectedly.
+///
+/// [1]: Communicator::spawn
+pub(super) type OnLost = Box<dyn FnOnce(Lost) + Send + 'static>;
+
+/// The reason that the [`incoming task`][1] terminated.
+///
+/// [1]: Communicator::spawn
+#[derive(Debug)]
+pub(super) enum Lost {
+    /// The [`Transport`] was lost unexpectedly (e.g. the device was unplugged).
+    Transport(io::Error),
+    /// The device sent `HW_DISCONNECT`, so the disconnect is orderly.
+    HwDisconnect,
+}
+
+/// Handles all incoming and outgoing commands between the host and an open [`Transport`].
 pub(super) struct Communicator<const CH: usize> {
     /// A thread-safe message [`Dispatcher`] for handling async `Req → Get` callback patterns.
     dispatcher: Dispatcher<CH>,
-    /// An async background task that handles a stream of incoming commands from the [`Interface`].
-    #[allow(unused)]
+    /// An async background task that handles a stream of incoming commands from the
+    /// [`Transport`].
     incoming: Task<()>,
-    /// An [`outgoing`][1] [`Bulk`][2] [`Endpoint`][3] for sending commands to the USB
-    /// [`Interface`]. Protected by a [`Mutex`] for async access.
-    ///
-    /// [1]: Out
-    /// [2]: Bulk
-    /// [3]: Endpoint
-    pub(super) outgoing: Mutex<Endpoint<Bulk, Out>>,
+    /// An async background task that acknowledges update messages. See [`Self::keep_alive`].
+    keep_alive: Task<()>,
+    /// The open [`Transport`] used for sending commands to the device.
+    transport: SharedTransport,
+    /// Set to `True` if the [`incoming task`][Self::spawn] terminates unexpectedly.
+    lost: Arc<AtomicBool>,
 }
 
 impl<const CH: usize> Communicator<CH> {
-    /// Creates a new [`Communicator`] instance for the specified USB [`Interface`].
-    pub(super) async fn new(interface: Interface, dispatcher: Dispatcher<CH>) -> Self {
+    /// Opens the [`Transport`] and creates a new [`Communicator`] instance.
+    ///
+    /// The `on_lost` callback is invoked if the [`Transport`] is lost unexpectedly, or if the
+    /// device sends `HW_DISCONNECT`.
+    pub(super) async fn new(
+        transport: SharedTransport,
+        dispatcher: Dispatcher<CH>,
+        on_lost: OnLost,
+    ) -> Result<Self, io::Error> {
         log::debug!("{dispatcher} COMMUNICATOR::NEW (requested)");
-        serial_port::init(&interface).await;
+        let stream = transport.lock().await.open().await?;
         let dsp = dispatcher.clone(); // Inexpensive Arc Clone
-        let endpoint = interface
-            .endpoint(OUT_ENDPOINT)
-            .unwrap_or_else(|e| abort(format!("Failed to open OUT endpoint : {e}")));
-        let outgoing = Mutex::new(endpoint);
-        let incoming = Self::spawn(interface, dsp);
+        let lost = Arc::new(AtomicBool::new(false));
+        let incoming = Self::spawn(stream, dsp, lost.clone(), on_lost);
+        let keep_alive = Self::keep_alive(transport.clone(), dispatcher.clone());
         log::debug!("{dispatcher} COMMUNICATOR::NEW (success)");
-        Self {
+        Ok(Self {
             dispatcher,
             incoming,
-            outgoing,
-        }
+            keep_alive,
+            transport,
+            lost,
+        })
     }
 
-    /// Handles any [`TransferError`] returned from the [`incoming task`][Self::spawn].
-    // NOTE: If the incoming task terminates, it cannot be restarted without the [`Interface`].
-    // Automatic recovery may be implemented in a future version of Thormotion.
-    fn handle_error(error: TransferError) {
-        // NOTE: Currently, all errors cause the program to abort. A `match` statement allows
-        // TransferError variants to be handled differently if required.
-        match error {
-            _ => abort(format!("Background task error : {}", error)),
+    /// Handles the termination of the [`incoming task`][Self::spawn].
+    ///
+    /// The [`Communicator`] is marked as lost, then the `on_lost` callback is invoked. The
+    /// [`Transport`] must be reopened to restart the incoming task.
+    fn handle_lost(dispatcher: &Dispatcher<CH>, reason: Lost, lost: &AtomicBool, f: OnLost) {
+        match &reason {
+            Lost::Transport(error) => log::warn!("{dispatcher} BACKGROUND (lost) {error}"),
+            Lost::HwDisconnect => log::info!("{dispatcher} BACKGROUND (stopped) HW_DISCONNECT"),
         }
+        lost.store(true, Ordering::SeqCst);
+        f(reason);
+    }
+
+    /// Returns `True` if the [`incoming task`][Self::spawn] terminated unexpectedly.
+    pub(super) fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::SeqCst)
     }
 
     /// Spawns an async background task that handles a stream of incoming commands from the
-    /// [`Interface`].
+    /// [`Transport`].
+    ///
+    /// Incoming bytes are framed into messages using the [`length`] encoded in each header, so
+    /// that messages with unknown IDs do not desynchronise the stream. Messages with an invalid
+    /// destination or source byte are discarded. See [`apt::validate`].
     ///
     /// The task loops indefinitely until either:
     /// 1. It is explicitly [`cancelled`][Task::cancel]
     /// 2. The [`Communicator`] is dropped
-    /// 3. A [`TransferError`] occurs. See [`Self::handle_error`].
-    fn spawn(interface: Interface, dispatcher: Dispatcher<CH>) -> Task<()> {
+    /// 3. The [`Incoming`] stream ends or returns an [`io::Error`]. See [`Self::handle_lost`].
+    /// 4. The device sends `HW_DISCONNECT`. This is reported as [`Lost::HwDisconnect`], after
+    ///    the message is dispatched.
+    fn spawn(
+        mut stream: Incoming,
+        dispatcher: Dispatcher<CH>,
+        lost: Arc<AtomicBool>,
+        on_lost: OnLost,
+    ) -> Task<()> {
+        let dsp = dispatcher.clone(); // Inexpensive Arc Clone
         log::debug!("{dispatcher} SPAWN (requested)");
-        let mut endpoint: Endpoint<Bulk, In> = interface
-            .endpoint(IN_ENDPOINT)
-            .unwrap_or_else(|e| abort(format!("Failed to open IN endpoint : {e}")));
-        // Buffer size must be a nonzero multiple of the endpoint's maximum packet size
-        let pkt_size = endpoint.max_packet_size();
-        let buf_size = CMD_LEN_MAX.div_ceil(pkt_size) * pkt_size;
-        while endpoint.pending() < N_TRANSFERS {
-            endpoint.submit(Buffer::new(buf_size));
-        }
-        let mut queue: VecDeque<u8> = VecDeque::with_capacity(N_TRANSFERS * CMD_LEN_MAX);
-        let mut id = [0u8; 2]; // Reusable ID buffer
-        let mut listen = async move || -> Result<(), TransferError> {
+        let mut queue: VecDeque<u8> = VecDeque::with_capacity(CMD_LEN_MAX);
+        let mut header = [0u8; 6]; // Reusable header buffer
+        // Returns Ok if the device sends HW_DISCONNECT
+        let mut listen = async move || -> Result<(), io::Error> {
             log::debug!("{dispatcher} SPAWN (starting background task)");
             loop {
-                smol::Timer::after(TIME).await;
-                let mut completion = endpoint.next_complete().await;
-                if completion.actual_len > 2 {
-                    completion.status?;
+                // Completion-driven. The task is woken as soon as the Transport yields new bytes.
+                let bytes = match stream.next().await {
+                    Some(result) => result?,
+                    None => return Err(io::ErrorKind::UnexpectedEof.into()),
+                };
+                log::trace!(
+                    "BACKGROUND {} RECEIVED {:02X?}",
+                    dispatcher.serial_number(),
+                    bytes,
+                );
+                queue.extend(bytes); // Copy u8 bytes into queue ring buffer
+                while queue.get(5).is_some() {
+                    // Copying is more efficient than borrowing for u8. Copied bytes remain in queue
+                    header.iter_mut().zip(&queue).for_each(|(h, q)| *h = *q);
+                    let len = length(&header);
                     log::trace!(
-                        "BACKGROUND {} RECEIVED {:02X?}",
+                        "BACKGROUND {} MESSAGE ID {:02X?} LENGTH {}",
                         dispatcher.serial_number(),
-                        &completion.buffer[2..],
+                        [header[0], header[1]],
+                        len,
                     );
-                    let iter = completion.buffer.iter().skip(2); // Skip prefix bytes
-                    queue.extend(iter); // Copy u8 bytes into queue ring buffer
-                    while queue.get(5).is_some() {
-                        id[0] = queue[0]; // Copying is more efficient than borrowing for u8
-                        id[1] = queue[1]; // Copied bytes remain in queue
-                        log::trace!(
-                            "BACKGROUND {} MESSAGE ID {:02X?}",
+                    if len > CMD_LEN_MAX {
+                        // The header is corrupt, so the message boundary is lost. Discard the queue
+                        log::warn!(
+                            "BACKGROUND {} INVALID HEADER {:02X?} (discarding {} bytes)",
                             dispatcher.serial_number(),
-                            id
+                            header,
+                            queue.len(),
                         );
-                        let len = dispatcher.length(&id).await;
-                        if queue.len() < len {
-                            log::trace!(
-                                "BACKGROUND {} INCOMPLETE (waiting) QUEUE {} REQUIRE {}",
-                                dispatcher.serial_number(),
-                                queue.len(),
-                                len,
-                            );
-                            break;
-                        }
-                        let msg = queue.drain(..len).collect();
+                        queue.clear();
+                        break;
+                    }
+                    if queue.len() < len {
                         log::trace!(
-                            "BACKGROUND {} DISPATCH {:02X?}",
+                            "BACKGROUND {} INCOMPLETE (waiting) QUEUE {} REQUIRE {}",
                             dispatcher.serial_number(),
-                            msg
+                            queue.len(),
+                            len,
                         );
-                        dispatcher.dispatch(msg, CH).await;
+                        break;
+                    }
+                    let msg = queue.drain(..len).collect();
+                    if let Err(e) = apt::validate(&header) {
+                        log::warn!(
+                            "BACKGROUND {} INVALID ADDRESS {:02X?} (discarding) {e}",
+                            dispatcher.serial_number(),
+                            msg,
+                        );
+                        continue;
+                    }
+                    log::trace!(
+                        "BACKGROUND {} DISPATCH {:02X?}",
+                        dispatcher.serial_number(),
+                        msg
+                    );
+                    dispatcher.dispatch(msg).await;
+                    if [header[0], header[1]] == HW_DISCONNECT {
+                        return Ok(());
                     }
                 }
-                completion.buffer.clear(); // Clear the buffer for reuse
-                endpoint.submit(completion.buffer); // Resubmit buffer to endpoint
             }
         };
         smol::spawn(async move {
-            if let Err(error) = listen().await {
-                Self::handle_error(error);
+            let reason = match listen().await {
+                Ok(()) => Lost::HwDisconnect,
+                Err(error) => Lost::Transport(error),
+            };
+            Self::handle_lost(&dsp, reason, &lost, on_lost);
+        })
+    }
+
+    /// Spawns an async background task that sends `MOT_ACK_USTATUSUPDATE` to the device every
+    /// [`KEEP_ALIVE_INTERVAL`] while update messages are [`enabled`][1].
+    ///
+    /// Each addressed channel is acknowledged separately. For generic USB units, a single
+    /// acknowledgement covers every channel.
+    ///
+    /// Keep-alive messages are sent using [`Transport::keep_alive`] and recorded as
+    /// [`Direction::KeepAlive`]. See [`Self::transmit`]. Errors are logged and ignored. A lost
+    /// [`Transport`] is handled by the [`incoming task`][Self::spawn].
+    ///
+    /// [1]: Dispatcher::update_messages
+    fn keep_alive(transport: SharedTransport, dispatcher: Dispatcher<CH>) -> Task<()> {
+        let addressing = dispatcher.addressing();
+        let mut acks: Vec<Vec<u8>> = (1..=CH)
+            .filter_map(|channel| addressing.encode(&Message::MotAckUStatusUpdate, channel))
+            .collect();
+        acks.dedup();
+        smol::spawn(async move {
+            loop {
+                Timer::after(KEEP_ALIVE_INTERVAL).await;
+                if !dispatcher.update_messages() {
+                    continue;
+                }
+                for ack in &acks {
+                    log::trace!("{dispatcher} KEEP_ALIVE {ack:02X?}");
+                    let ack = ack.clone();
+                    let result =
+                        Self::transmit(&dispatcher, &transport, Direction::KeepAlive, ack).await;
+                    if let Err(e) = result {
+                        log::debug!("{dispatcher} KEEP_ALIVE (failed) {e}");
+                    }
+                }
             }
         })
     }
 
-    /// Send a command to the device [`Interface`].
-    pub(super) async fn send(&self, command: Vec<u8>) {
+    /// Send a command to the device [`Transport`].
+    pub(super) async fn send(&self, command: Vec<u8>) -> Result<(), io::Error> {
         log::trace!("{self} SEND (requested) {command:02X?}");
-        let buf = Buffer::from(command);
-        self.outgoing.lock().await.submit(buf);
+        Self::transmit(&self.dispatcher, &self.transport, Direction::Tx, command).await?;
         log::trace!("{self} SEND (success)");
+        Ok(())
+    }
+
+    /// Records the message if the [`Dispatcher`] is recording, then sends it to the device
+    /// [`Transport`].
+    ///
+    /// Messages sent as [`Direction::KeepAlive`] use [`Transport::keep_alive`], and messages sent
+    /// as [`Direction::Tx`] use [`Transport::send`].
+    ///
+    /// ### Panics
+    ///
+    /// Calls [`bug_abort`] if the direction is [`Direction::Rx`], because received messages are
+    /// never sent to the device.
+    async fn transmit(
+        dispatcher: &Dispatcher<CH>,
+        transport: &SharedTransport,
+        direction: Direction,
+        command: Vec<u8>,
+    ) -> Result<(), io::Error> {
+        dispatcher.capture(direction, &command);
+        let mut transport = transport.lock().await;
+        match direction {
+            Direction::Tx => transport.send(command).await,
+            Direction::KeepAlive => transport.keep_alive(command).await,
+            Direction::Rx => bug_abort(format!("{dispatcher} cannot transmit {command:02X?} (Rx)")),
+        }
+    }
+
+    /// Stops the [`incoming task`][Self::spawn] and closes the [`Transport`].
+    pub(super) async fn close(self) -> Result<(), io::Error> {
+        log::debug!("{self} CLOSE (requested)");
+        let Self {
+            dispatcher,
+            incoming,
+            keep_alive,
+            transport,
+            ..
+        } = self;
+        keep_alive.cancel().await;
+        incoming.cancel().await;
+        transport.lock().await.close().await?;
+        log::debug!("{dispatcher} CLOSE (success)");
+        Ok(())
     }
 
     /// Returns the [`Dispatcher`] wrapped in an [`Arc`][std::sync::Arc].
@@ -9,61 +9,89 @@ modification, are permitted provided that the conditions of the LICENSE are met.
 */
 
 mod communicator;
-mod serial_port;
 mod status;
 
 use std::fmt::{Display, Formatter};
 use std::hash::Hash;
 use std::io;
-use std::ops::Deref;
+use std::path::Path;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
 
-use communicator::Communicator;
+use communicator::{Communicator, Lost, OnLost, SharedTransport};
 use log;
-use nusb::DeviceInfo;
-use smol::lock::RwLock;
+use smol::Timer;
+use smol::lock::{Mutex, RwLock};
 use status::Status;
 
-use crate::devices::{abort, abort_device, get_device, remove_device};
-use crate::error::{cmd, sn};
-use crate::messages::{Dispatcher, Metadata, Provenance};
+use crate::apt::{Addressing, Message, UStatus};
+use crate::devices::{abort_device, escalate, remove_device};
+use crate::error::{DeviceFault, Error, cmd};
+use crate::messages::{Dispatcher, Event, Metadata, Provenance, Update};
+use crate::traits::Transport;
+
+/// Delay between automatic reconnection attempts after the device is disconnected.
+const RECONNECT_DELAY: Duration = Duration::from_millis(250);
+
+/// The device [`Status`] shared between the [`UsbPrimitive`] and its background tasks.
+type SharedStatus<const CH: usize> = Arc<RwLock<Status<CH>>>;
 
 #[derive(Debug)]
-pub(crate) struct UsbPrimitive<const CH: usize> {
+pub struct UsbPrimitive<const CH: usize> {
     /// A unique eight-digit serial number that is printed on the Thorlabs device.
     serial_number: String,
-    /// Information about a device that can be obtained without calling [`DeviceInfo::open`].
-    device_info: DeviceInfo,
+    /// How the host addresses the channels of the device. See [`encode`][1].
+    ///
+    /// [1]: UsbPrimitive::encode
+    addressing: Addressing,
+    /// The [`Transport`] used to communicate with the device.
+    transport: SharedTransport,
     /// The current device status.
     ///
     /// - [`Open`][1] → Contains an active [`Communicator`]
     /// - [`Closed`][2] → Contains an idle [`Dispatcher`]
+    /// - [`Disconnected`][4] → Contains an idle [`Dispatcher`] and a reconnection task
     ///
     /// Open the device by calling [`open`][3].
     ///
     /// [1]: Status::Open
     /// [2]: Status::Closed
     /// [3]: UsbPrimitive::open
-    status: RwLock<Status<CH>>,
+    /// [4]: Status::Disconnected
+    status: SharedStatus<CH>,
+    /// The default time to wait for a response from the device. Waits indefinitely if [`None`].
+    ///
+    /// Set the default timeout by calling [`set_timeout`][1].
+    ///
+    /// [1]: UsbPrimitive::set_timeout
+    timeout: RwLock<Option<Duration>>,
 }
 
 impl<const CH: usize> UsbPrimitive<CH> {
     /// Constructs a new [`UsbPrimitive`] for a Thorlabs device with the specified serial number.
     ///
-    /// Returns [`Error::NotFound`] if the specified device is not connected.
+    /// The device communicates through the provided [`Transport`], which is opened when
+    /// [`open`][1] is called. Messages are addressed to each channel as described by
+    /// [`Addressing`].
     ///
-    /// Returns [`Error::Multiple`] if more than one device with the specified serial number is
-    /// found.
-    pub(super) fn new(serial_number: &String, ids: &[Metadata<CH>]) -> Result<Self, sn::Error> {
+    /// [1]: UsbPrimitive::open
+    pub(super) fn new(
+        serial_number: &str,
+        ids: &[Metadata<CH>],
+        addressing: Addressing,
+        transport: Box<dyn Transport>,
+    ) -> Self {
         log::debug!("USB Primitive {serial_number} NEW (requested)");
-        let device_info = get_device(serial_number)?;
-        log::debug!("USB Primitive {serial_number} NEW (found)");
+        let dispatcher = Dispatcher::new(ids, serial_number, addressing);
         let device = Self {
-            serial_number: serial_number.clone(),
-            device_info,
-            status: RwLock::new(Status::Closed(Dispatcher::new(ids, serial_number))),
+            serial_number: serial_number.to_string(),
+            addressing,
+            transport: Arc::new(Mutex::new(transport)),
+            status: Arc::new(RwLock::new(Status::Closed(dispatcher))),
+            timeout: RwLock::new(None),
         };
         log::debug!("USB Primitive {serial_number} NEW (success)");
-        Ok(device)
+        device
     }
 
     /// Returns the serial number of the device as a `&str`.
@@ -71,59 +99,213 @@ impl<const CH: usize> UsbPrimitive<CH> {
         &self.serial_number
     }
 
+    /// Encodes a message sent from the host to the specified channel, using the destination byte
+    /// for the channel. Channel `0` refers to the whole device.
+    ///
+    /// Returns [`cmd::Error::InvalidChannel`] if the channel cannot be addressed. See
+    /// [`Addressing::encode`].
+    pub(crate) fn encode(&self, message: &Message, channel: usize) -> Result<Vec<u8>, Error> {
+        self.addressing
+            .encode(message, channel)
+            .ok_or_else(|| escalate(cmd::Error::InvalidChannel(channel)))
+    }
+
+    /// Returns the destination byte for messages sent to the specified channel.
+    ///
+    /// Returns [`cmd::Error::InvalidChannel`] if the channel cannot be addressed. See
+    /// [`Addressing::destination`].
+    pub(crate) fn destination(&self, channel: usize) -> Result<u8, Error> {
+        self.addressing
+            .destination(channel)
+            .ok_or_else(|| escalate(cmd::Error::InvalidChannel(channel)))
+    }
+
+    /// Returns the default time to wait for a response from the device.
+    ///
+    /// Returns [`None`] if the device waits indefinitely.
+    pub(crate) async fn timeout(&self) -> Option<Duration> {
+        *self.timeout.read().await
+    }
+
+    /// Sets the default time to wait for a response from the device. Pass [`None`] to wait
+    /// indefinitely.
+    pub(super) async fn set_timeout(&self, timeout: Option<Duration>) {
+        log::debug!("{self} SET_TIMEOUT {timeout:?}");
+        *self.timeout.write().await = timeout;
+    }
+
     /// Returns `True` if the device is open.
     pub(super) async fn is_open(&self) -> bool {
         match *self.status.read().await {
             Status::Open(_) => true,
-            Status::Closed(_) => false,
+            Status::Closed(_) | Status::Disconnected(..) => false,
         }
     }
 
-    /// Opens an [`Interface`][1] to the [`USB Device`][2].
+    /// Opens the [`Transport`] to the [`USB Device`][1].
     ///
-    /// No action is taken if the device [`Status`] is already [`Open`][3].
+    /// No action is taken if the device [`Status`] is already [`Open`][2]. If the device is
+    /// [`Disconnected`][3], automatic reconnection is cancelled before opening.
     ///
-    /// [1]: nusb::Interface
-    /// [2]: UsbPrimitive
-    /// [3]: Status::Open
+    /// [1]: UsbPrimitive
+    /// [2]: Status::Open
+    /// [3]: Status::Disconnected
     pub(super) async fn open(&self) -> Result<(), io::Error> {
         log::debug!("{self} OPEN (requested)");
         let mut guard = self.status.write().await;
-        if let Status::Closed(dsp) = guard.deref() {
+        Self::cancel_reconnect(&mut guard).await;
+        if let Status::Closed(dsp) = &*guard {
             log::debug!("{self} OPEN (is closed)");
-            let interface = self
-                .device_info
-                .open()
-                .await?
-                .detach_and_claim_interface(0)
-                .await?;
+            let transport = self.transport.clone(); // Inexpensive Arc Clone
             let dispatcher = dsp.clone(); // Inexpensive Arc Clone
-            let communicator = Communicator::new(interface, dispatcher).await;
+            let on_lost = Self::on_lost(&self.status, &self.transport);
+            let communicator = Communicator::new(transport, dispatcher, on_lost).await?;
             *guard = Status::Open(communicator);
         }
         log::debug!("{self} OPEN (success)");
         Ok(())
     }
 
-    /// Releases the claimed [`Interface`][1] to the [`USB Device`][2].
+    /// Closes the [`Transport`] to the [`USB Device`][1].
     ///
-    /// No action is taken if the device [`Status`] is already [`Closed`][3].
+    /// No action is taken if the device [`Status`] is already [`Closed`][2]. If the device is
+    /// [`Disconnected`][3], automatic reconnection is cancelled.
     ///
-    /// [1]: nusb::Interface
-    /// [2]: UsbPrimitive
-    /// [3]: Status::Closed
+    /// [1]: UsbPrimitive
+    /// [2]: Status::Closed
+    /// [3]: Status::Disconnected
     pub(super) async fn close(&self) -> Result<(), io::Error> {
         log::debug!("{self} CLOSE (requested)");
         let mut guard = self.status.write().await;
-        if let Status::Open(communicator) = guard.deref() {
+        Self::cancel_reconnect(&mut guard).await;
+        if let Status::Open(communicator) = &*guard {
             log::debug!("{self} CLOSE (is open)");
-            let dispatcher = communicator.get_dispatcher();
-            *guard = Status::Closed(dispatcher);
+            let idle = Status::Closed(communicator.get_dispatcher());
+            if let Status::Open(communicator) = std::mem::replace(&mut *guard, idle) {
+                communicator.close().await?;
+            }
         }
         log::debug!("{self} CLOSE (success)");
         Ok(())
     }
 
+    /// Replaces a [`Disconnected`][1] status with [`Closed`][2] and cancels the reconnection task.
+    ///
+    /// No action is taken if the device is not [`Disconnected`][1].
+    ///
+    /// [1]: Status::Disconnected
+    /// [2]: Status::Closed
+    async fn cancel_reconnect(status: &mut Status<CH>) {
+        if let Status::Disconnected(dsp, _) = status {
+            let idle = Status::Closed(dsp.clone()); // Inexpensive Arc Clone
+            if let Status::Disconnected(dsp, task) = std::mem::replace(status, idle) {
+                log::debug!("{dsp} RECONNECT (cancelled)");
+                task.cancel().await;
+            }
+        }
+    }
+
+    /// Returns a callback which is invoked by the [`Communicator`] if the [`Transport`] is lost
+    /// unexpectedly, or if the device sends `HW_DISCONNECT`. See [`disconnect`][1].
+    ///
+    /// Holds a [`Weak`] reference to the device [`Status`], so that the callback does not keep a
+    /// dropped device alive.
+    ///
+    /// [1]: UsbPrimitive::disconnect
+    fn on_lost(status: &SharedStatus<CH>, transport: &SharedTransport) -> OnLost {
+        let status = Arc::downgrade(status);
+        let transport = transport.clone(); // Inexpensive Arc Clone
+        Box::new(move |lost| smol::spawn(Self::disconnect(status, transport, lost)).detach())
+    }
+
+    /// Closes the lost [`Communicator`] after the [`Transport`] is lost, or after the device sends
+    /// `HW_DISCONNECT`.
+    ///
+    /// 1. The lost [`Communicator`] is closed
+    /// 2. Any functions awaiting a command response are woken with a closed channel
+    /// 3. If the [`Transport`] was lost, the device moves to the [`Disconnected`][1] status and a
+    ///    background task is spawned to [`reconnect`][2] the device. After `HW_DISCONNECT`, the
+    ///    device remains [`Closed`][3] until it is explicitly reopened.
+    ///
+    /// No action is taken if the device was closed or reopened in the meantime.
+    ///
+    /// [1]: Status::Disconnected
+    /// [2]: UsbPrimitive::reconnect
+    /// [3]: Status::Closed
+    async fn disconnect(status: Weak<RwLock<Status<CH>>>, transport: SharedTransport, lost: Lost) {
+        let Some(shared) = status.upgrade() else {
+            return; // Device dropped
+        };
+        let mut guard = shared.write().await;
+        let dispatcher = match &*guard {
+            Status::Open(communicator) if communicator.is_lost() => communicator.get_dispatcher(),
+            _ => return,
+        };
+        let idle = Status::Closed(dispatcher.clone()); // Inexpensive Arc Clone
+        if let Status::Open(communicator) = std::mem::replace(&mut *guard, idle) {
+            communicator
+                .close()
+                .await
+                .unwrap_or_else(|e| log::warn!("{dispatcher} DISCONNECT CLOSE (failed) {e}"));
+        }
+        dispatcher.disconnect().await;
+        if let Lost::HwDisconnect = lost {
+            log::info!("{dispatcher} CLOSED (HW_DISCONNECT)");
+            return;
+        }
+        log::warn!("{dispatcher} DISCONNECTED");
+        let dsp = dispatcher.clone(); // Inexpensive Arc Clone
+        let task = smol::spawn(Self::reconnect(status, transport, dsp));
+        *guard = Status::Disconnected(dispatcher, task);
+    }
+
+    /// Waits for the [`Transport`] to become available again, then reopens the device and
+    /// reattaches the existing [`Dispatcher`].
+    ///
+    /// Loops until the device is reopened, unless the [`Transport`] does not support
+    /// [`reconnect`][1]. In that case, the device remains [`Disconnected`][2] until it is
+    /// explicitly reopened.
+    ///
+    /// [1]: Transport::reconnect
+    /// [2]: Status::Disconnected
+    async fn reconnect(
+        status: Weak<RwLock<Status<CH>>>,
+        transport: SharedTransport,
+        dispatcher: Dispatcher<CH>,
+    ) {
+        loop {
+            Timer::after(RECONNECT_DELAY).await;
+            log::debug!("{dispatcher} RECONNECT (waiting)");
+            if let Err(e) = transport.lock().await.reconnect().await {
+                if e.kind() == io::ErrorKind::Unsupported {
+                    log::warn!("{dispatcher} RECONNECT (unsupported)");
+                    return;
+                }
+                log::debug!("{dispatcher} RECONNECT (failed) {e}");
+                continue;
+            }
+            let Some(shared) = status.upgrade() else {
+                return; // Device dropped
+            };
+            let mut guard = shared.write().await;
+            let on_lost = Self::on_lost(&shared, &transport);
+            let tsp = transport.clone(); // Inexpensive Arc Clone
+            let dsp = dispatcher.clone(); // Inexpensive Arc Clone
+            match Communicator::new(tsp, dsp, on_lost).await {
+                Ok(communicator) => {
+                    if let Status::Disconnected(_, task) =
+                        std::mem::replace(&mut *guard, Status::Open(communicator))
+                    {
+                        task.detach(); // This task is about to complete
+                    }
+                    log::info!("{dispatcher} RECONNECT (success)");
+                    return;
+                }
+                Err(e) => log::debug!("{dispatcher} RECONNECT OPEN (failed) {e}"),
+            }
+        }
+    }
+
     /// Safely brings the [`USB Device`][1] to a resting state and releases the claimed
     /// [`Interface`][2].
     ///
@@ -143,10 +325,68 @@ impl<const CH: usize> UsbPrimitive<CH> {
     /// [5]: crate::devices::utils::DEVICES
     /// [6]: ahash::HashMap
     /// [7]: UsbPrimitive::close
+    #[allow(unused)]
     async fn abort(&self) {
         abort_device(self.serial_number());
     }
 
+    /// Starts recording every message sent and received by the host to a new session file at the
+    /// specified path. Replaces any existing recording.
+    ///
+    /// The recording continues when the device is closed and reopened.
+    pub(super) async fn record(&self, path: &Path) -> Result<(), io::Error> {
+        self.status.read().await.dispatcher().record(path)
+    }
+
+    /// Stops recording. No action is taken if the device is not recording.
+    pub(super) async fn stop_recording(&self) {
+        self.status.read().await.dispatcher().stop_recording()
+    }
+
+    /// Returns a new subscriber to every [`DeviceFault`] reported by the device.
+    ///
+    /// See [`Dispatcher::faults`].
+    pub(super) async fn faults(&self) -> async_broadcast::Receiver<DeviceFault> {
+        self.status.read().await.dispatcher().faults()
+    }
+
+    /// Returns a new subscriber to a copy of every message received from the device.
+    ///
+    /// See [`Dispatcher::events`].
+    pub(super) async fn events(&self) -> async_broadcast::Receiver<Event> {
+        self.status.read().await.dispatcher().events()
+    }
+
+    /// Records whether update messages are enabled.
+    ///
+    /// See [`Dispatcher::set_update_messages`].
+    pub(crate) async fn set_update_messages(&self, enabled: bool) {
+        self.status
+            .read()
+            .await
+            .dispatcher()
+            .set_update_messages(enabled)
+    }
+
+    /// Returns the most recent status reported by the device on the given channel, if it was
+    /// received within `max_age`.
+    ///
+    /// See [`Dispatcher::cached`].
+    pub(crate) async fn cached(&self, channel: usize, max_age: Duration) -> Option<UStatus> {
+        self.status
+            .read()
+            .await
+            .dispatcher()
+            .cached(channel, max_age)
+    }
+
+    /// Returns a new subscriber to every status update received from the device.
+    ///
+    /// See [`Dispatcher::updates`].
+    pub(crate) async fn updates(&self) -> async_broadcast::Receiver<Update> {
+        self.status.read().await.dispatcher().updates()
+    }
+
     /// Returns a receiver for the given command ID, wrapped in the [`Provenance`] enum. This is
     /// useful for pattern matching.
     ///
@@ -163,7 +403,7 @@ impl<const CH: usize> UsbPrimitive<CH> {
     /// [2]: Provenance::Existing
     /// [3]: Dispatcher::any_receiver
     /// [4]: Dispatcher::new_receiver
-    pub(crate) async fn receiver(&self, id: &[u8], channel: usize) -> Provenance {
+    pub(crate) async fn receiver(&self, id: &[u8], channel: usize) -> Result<Provenance, Error> {
         log::debug!("{self} CHANNEL {channel} RECEIVER {id:02X?} (requested)");
         self.status
             .read()
@@ -175,37 +415,74 @@ impl<const CH: usize> UsbPrimitive<CH> {
 
     /// Returns a [`Receiver`] for the given command ID. Guarantees that the device is not
     /// currently executing the command for the given ID.
-    pub(crate) async fn new_receiver(&self, id: &[u8], channel: usize) -> Provenance {
+    ///
+    /// Waits for any pending command to complete using the default [`timeout`][1].
+    ///
+    /// [1]: UsbPrimitive::timeout
+    pub(crate) async fn new_receiver(
+        &self,
+        id: &[u8],
+        channel: usize,
+    ) -> Result<Provenance, Error> {
         log::debug!("{self} CHANNEL {channel} NEW_RECEIVER (requested)");
-        self.status
-            .read()
-            .await
-            .dispatcher()
-            .new_receiver(id, channel)
-            .await
+        let timeout = self.timeout().await;
+        let dispatcher = self.status.read().await.dispatcher();
+        dispatcher.new_receiver(id, channel, timeout).await
     }
 
-    /// Sends a command to the device.
-    pub(crate) async fn send(&self, command: Vec<u8>) {
-        log::debug!("{self} SEND (requested)");
-        self.try_send(command)
-            .await
-            .unwrap_or_else(|e| abort(format!("{self} SEND (failed) {e}")));
+    /// Consumes the [`Provenance`], returning the command response. Waits for the default
+    /// [`timeout`][1].
+    ///
+    /// [1]: UsbPrimitive::timeout
+    pub(crate) async fn receive(
+        &self,
+        rx: Provenance,
+        id: &[u8],
+        channel: usize,
+    ) -> Result<Arc<[u8]>, Error> {
+        let timeout = self.timeout().await;
+        self.receive_timeout(rx, id, channel, timeout).await
     }
 
-    /// Sends a command to the device.
+    /// Consumes the [`Provenance`], returning the command response. Returns
+    /// [`cmd::Error::Timeout`] if no response arrives within the specified `timeout`.
+    ///
+    /// See [`Dispatcher::receive`].
+    pub(crate) async fn receive_timeout(
+        &self,
+        rx: Provenance,
+        id: &[u8],
+        channel: usize,
+        timeout: Option<Duration>,
+    ) -> Result<Arc<[u8]>, Error> {
+        // Clone the Dispatcher so that the status lock is not held while awaiting the response
+        let dispatcher = self.status.read().await.dispatcher();
+        dispatcher.receive(rx, id, channel, timeout).await
+    }
+
+    /// Removes the broadcast channel for the given command ID if no functions are awaiting the
+    /// command response. Used after a [`Provenance`] is dropped without receiving the response.
     ///
-    /// Returns an [`Error`][1] if the device is closed.
+    /// See [`Dispatcher::release`].
+    pub(crate) async fn release(&self, id: &[u8], channel: usize) -> Result<(), Error> {
+        let dispatcher = self.status.read().await.dispatcher();
+        dispatcher.release(id, channel).await
+    }
+
+    /// Sends a command to the device.
     ///
-    /// [1]: cmd::Error
-    pub(crate) async fn try_send(&self, command: Vec<u8>) -> Result<(), cmd::Error> {
+    /// Returns [`cmd::Error::DeviceClosed`] or [`cmd::Error::DeviceDisconnected`] if the device is
+    /// not open, or [`Error::Io`] if the [`Transport`] fails to send the command.
+    pub(crate) async fn send(&self, command: Vec<u8>) -> Result<(), Error> {
+        log::debug!("{self} SEND (requested)");
         let guard = self.status.read().await;
         match &*guard {
-            Status::Open(communicator) => {
-                communicator.send(command).await;
-                Ok(())
-            }
-            Status::Closed(_) => Err(cmd::Error::DeviceClosed),
+            Status::Open(communicator) => communicator.send(command).await.map_err(|e| {
+                log::warn!("{self} SEND (failed) {e}");
+                escalate(e)
+            }),
+            Status::Closed(_) => Err(escalate(cmd::Error::DeviceClosed)),
+            Status::Disconnected(..) => Err(escalate(cmd::Error::DeviceDisconnected)),
         }
     }
 }
@@ -213,12 +490,9 @@ impl<const CH: usize> UsbPrimitive<CH> {
 impl<const CH: usize> PartialEq<UsbPrimitive<CH>> for UsbPrimitive<CH> {
     /// Compares two `UsbPrimitive` devices for equality.
     ///
-    /// Returns `true` if both devices have the same vendor ID, product ID, and serial number.
+    /// Returns `true` if both devices have the same serial number.
     fn eq(&self, other: &Self) -> bool {
-        self.device_info.vendor_id() == other.device_info.vendor_id()
-            && self.device_info.product_id() == other.device_info.product_id()
-            && self.device_info.serial_number().unwrap_or("")
-                == other.device_info.serial_number().unwrap_or("")
+        self.serial_number == other.serial_number
     }
 }
 
@@ -231,15 +505,13 @@ impl<const CH: usize> Eq for UsbPrimitive<CH> {}
 /// Implements the `Hash` trait for `UsbPrimitive`.
 ///
 /// This allows `UsbPrimitive` to be used as a key in hash-based collections like `HashMap`.
-/// The hash is computed based on the device's vendor ID, product ID, and serial number.
+/// The hash is computed based on the device's serial number.
 impl<const CH: usize> Hash for UsbPrimitive<CH> {
     /// Computes a hash value for the `UsbPrimitive` device.
     ///
-    /// The hash is based on the device's vendor ID, product ID, and serial number.
+    /// The hash is based on the device's serial number.
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.device_info.vendor_id().hash(state);
-        self.device_info.product_id().hash(state);
-        self.device_info.serial_number().unwrap_or("").hash(state);
+        self.serial_number.hash(state);
     }
 }
 
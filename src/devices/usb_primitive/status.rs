@@ -11,6 +11,8 @@ modification, are permitted provided that the conditions of the LICENSE are met.
 use std::fmt;
 use std::fmt::Display;
 
+use smol::Task;
+
 use super::communicator::Communicator;
 use crate::messages::Dispatcher;
 
@@ -18,12 +20,14 @@ use crate::messages::Dispatcher;
 ///
 /// - [`Open`][1] → Contains an active [`Communicator`]
 /// - [`Closed`][2] → Contains an idle [`Dispatcher`]
+/// - [`Disconnected`][4] → Contains an idle [`Dispatcher`] and a reconnection [`Task`]
 ///
 /// Open the device by calling [`open`][3]
 ///
 /// [1]: Status::Open
 /// [2]: Status::Closed
 /// [3]: crate::devices::UsbPrimitive::open
+/// [4]: Status::Disconnected
 #[derive(Debug)]
 pub(super) enum Status<const CH: usize> {
     /// The [`Interface`][1] is `open` and communicating.
@@ -38,16 +42,25 @@ pub(super) enum Status<const CH: usize> {
     ///
     /// [1]: nusb::Interface
     Closed(Dispatcher<CH>),
+    /// The [`Transport`][1] was lost unexpectedly (e.g. the device was unplugged).
+    ///
+    /// This enum variant contains an idle [`Dispatcher`] and a background [`Task`] which reopens
+    /// the device when it becomes available again.
+    ///
+    /// [1]: crate::traits::Transport
+    Disconnected(Dispatcher<CH>, Task<()>),
 }
 
 impl<const CH: usize> Status<CH> {
     /// Returns a string representation of the current status.
     ///
-    /// Returns "Open" if the device is open, or "Closed" if the device is closed.
+    /// Returns "Open" if the device is open, "Closed" if the device is closed, or "Disconnected"
+    /// if the device was lost unexpectedly.
     pub(super) fn as_str(&self) -> &str {
         match self {
             Self::Open(_) => "Open",
             Self::Closed(_) => "Closed",
+            Self::Disconnected(..) => "Disconnected",
         }
     }
 
@@ -56,6 +69,7 @@ impl<const CH: usize> Status<CH> {
         match self {
             Status::Open(communicator) => communicator.get_dispatcher(),
             Status::Closed(dispatcher) => dispatcher.clone(), // Inexpensive Arc Clone
+            Status::Disconnected(dispatcher, _) => dispatcher.clone(), // Inexpensive Arc Clone
         }
     }
 }
@@ -9,28 +9,30 @@ modification, are permitted provided that the conditions of the LICENSE are met.
 */
 
 use std::fmt::Display;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Mutex, MutexGuard, OnceLock};
 
 use ahash::{HashMap, HashMapExt};
 use nusb::{DeviceInfo, list_devices};
 
-use crate::error::sn::Error;
+use crate::error::{Error, sn};
 
 /* ---------------------------------------------------------------------------- Public Functions */
 
 #[thormacros::sync]
 /// Returns an iterator over all connected Thorlabs USB devices.
-pub async fn get_devices_async() -> impl Iterator<Item = DeviceInfo> {
-    list_devices()
-        .await
-        .unwrap_or_else(|e| abort(format!("Failed to list devices due to OS error: {e}")))
-        .filter(is_thorlabs_vid)
+///
+/// Returns [`Error::Io`] if the operating system fails to list USB devices.
+pub async fn get_devices_async() -> Result<impl Iterator<Item = DeviceInfo>, Error> {
+    let devices = list_devices().await.map_err(io::Error::from)?;
+    Ok(devices.filter(is_thorlabs_vid))
 }
 
 #[thormacros::sync]
 /// For convenience, this function prints a list of connected Thorlabs USB devices to stdout.
-pub async fn show_devices_async() {
-    let devices = get_devices_async().await;
+pub async fn show_devices_async() -> Result<(), Error> {
+    let devices = get_devices_async().await?;
     for (index, device) in devices.enumerate() {
         println!();
         println!("┌─ Thorlabs Device {index}");
@@ -43,6 +45,19 @@ pub async fn show_devices_async() {
         println!("│");
         println!("└─");
     }
+    Ok(())
+}
+
+/// Enables or disables the process-wide abort when a device function returns an [`Error`].
+///
+/// Disabled by default, in which case errors are returned to the caller. When enabled, any
+/// [`Error`] raised while communicating with a device first brings every connected device to a
+/// controlled stop, then terminates the program. This was the default behaviour before
+/// device functions returned a [`Result`].
+///
+/// Genuinely unrecoverable situations (e.g. a poisoned mutex) always abort.
+pub fn abort_on_error(enable: bool) {
+    ABORT_ON_ERROR.store(enable, Ordering::SeqCst);
 }
 
 /* --------------------------------------------------------------------------- Private Functions */
@@ -69,7 +84,20 @@ fn is_thorlabs_vid(device: &DeviceInfo) -> bool {
 /// [4]: crate::devices::UsbPrimitive::close
 /// [5]: crate::devices::UsbPrimitive::send
 #[doc(hidden)]
-static DEVICES: OnceLock<Mutex<HashMap<String, Box<dyn Fn() + Send + 'static>>>> = OnceLock::new();
+static DEVICES: OnceLock<Mutex<HashMap<String, AbortFn>>> = OnceLock::new();
+
+/// Set to `True` when [`abort`] is first called.
+#[doc(hidden)]
+static ABORTING: AtomicBool = AtomicBool::new(false);
+
+/// Set to `True` by [`abort_on_error`].
+#[doc(hidden)]
+static ABORT_ON_ERROR: AtomicBool = AtomicBool::new(false);
+
+/// A boxed [`abort`][1] function stored in the global [`DEVICES`] [`HashMap`].
+///
+/// [1]: crate::traits::ThorlabsDevice::abort
+type AbortFn = Box<dyn Fn() + Send + 'static>;
 
 /// Returns a [`MutexGuard`] protecting access to the global [`DEVICES`][1] [`HashMap`]. The map is
 /// lazily initialised when first accessed.
@@ -81,7 +109,7 @@ static DEVICES: OnceLock<Mutex<HashMap<String, Box<dyn Fn() + Send + 'static>>>>
 ///
 /// [1]: DEVICES
 #[doc(hidden)]
-fn devices<'a>() -> MutexGuard<'a, HashMap<String, Box<dyn Fn() + Send + 'static>>> {
+fn devices<'a>() -> MutexGuard<'a, HashMap<String, AbortFn>> {
     DEVICES
         .get_or_init(|| Mutex::new(HashMap::new()))
         .lock()
@@ -91,6 +119,10 @@ fn devices<'a>() -> MutexGuard<'a, HashMap<String, Box<dyn Fn() + Send + 'static
 /// Adds a new [`Thorlabs Device`][1] `serial number` (key) and corresponding [`abort`][2] function
 /// (value) to the global [`DEVICES`][3] [`HashMap`].
 ///
+/// Any previous entry with the same `serial number` is replaced. The previous entry may hold the
+/// last reference to a device whose handle was dropped. Dropping it calls [`remove_device`], so it
+/// is dropped after the [`DEVICES`][3] guard is released and before the new entry is inserted.
+///
 /// [1]: crate::traits::ThorlabsDevice
 /// [2]: crate::traits::ThorlabsDevice::abort
 /// [3]: DEVICES
@@ -99,6 +131,8 @@ pub(super) fn add_device<F>(serial_number: String, f: F)
 where
     F: Fn() + Send + 'static,
 {
+    let previous = devices().remove(&serial_number);
+    drop(previous);
     devices().insert(serial_number, Box::new(f));
 }
 
@@ -140,6 +174,7 @@ pub(super) fn abort_device(serial_number: &String) {
 /// [2]: crate::traits::ThorlabsDevice::abort
 /// [3]: crate::devices::UsbPrimitive::close
 #[doc(hidden)]
+#[allow(unused)]
 fn leak_device(serial_number: &str) {
     devices().remove(serial_number);
 }
@@ -166,7 +201,12 @@ where
     A: Display,
 {
     log::error!("ABORT → {}", message);
-    devices().keys().for_each(abort_device);
+    // Devices are only aborted once. A nested abort (e.g. a device failing to stop) must not
+    // re-enter the DEVICES mutex.
+    if !ABORTING.swap(true, Ordering::SeqCst) {
+        let keys: Vec<String> = devices().keys().cloned().collect();
+        keys.iter().for_each(abort_device);
+    }
     panic!("\nProcess aborted due to error → {}\n", message);
 }
 
@@ -193,19 +233,36 @@ pub(crate) fn bug_abort(message: String) -> ! {
     ));
 }
 
+/// Returns the [`Error`] unchanged, unless [`abort_on_error`] is enabled. In that case, calls
+/// [`abort`] instead.
+///
+/// Used wherever a device function raises a new [`Error`].
+#[doc(hidden)]
+pub(crate) fn escalate<E>(error: E) -> Error
+where
+    E: Into<Error>,
+{
+    let error = error.into();
+    if ABORT_ON_ERROR.load(Ordering::SeqCst) {
+        abort(&error);
+    }
+    error
+}
+
 /// Returns [`DeviceInfo`] for the Thorlabs device with the specified serial number.
 ///
-/// Returns [`Error::NotFound`] if the specified device is not connected.
+/// Returns [`sn::Error::NotFound`] if the specified device is not connected.
 ///
-/// Returns [`Error::Multiple`] if more than one device with the specified serial number is found.
-pub(super) fn get_device(serial_number: &String) -> Result<DeviceInfo, Error> {
+/// Returns [`sn::Error::Multiple`] if more than one device with the specified serial number is
+/// found.
+pub(super) fn get_device(serial_number: &str) -> Result<DeviceInfo, Error> {
     let mut devices =
-        get_devices().filter(|dev| dev.serial_number().map_or(false, |sn| sn == serial_number));
+        get_devices()?.filter(|dev| dev.serial_number().is_some_and(|sn| sn == serial_number));
     match devices.next() {
-        None => Err(Error::NotFound(serial_number.clone())),
+        None => Err(sn::Error::NotFound(serial_number.to_string()).into()),
         Some(d) => match devices.next() {
             None => Ok(d),
-            Some(_) => Err(Error::Multiple(serial_number.clone())),
+            Some(_) => Err(sn::Error::Multiple(serial_number.to_string()).into()),
         },
     }
 }
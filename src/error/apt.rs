@@ -0,0 +1,77 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use std::fmt::{Display, Formatter};
+
+type Id = [u8; 2];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Truncated(usize),
+    InvalidLength {
+        id: Id,
+        expected: usize,
+        actual: usize,
+    },
+    InvalidValue {
+        id: Id,
+        value: u8,
+    },
+    InvalidAddress {
+        id: Id,
+        destination: u8,
+        source: u8,
+    },
+    UnexpectedMessage(Id),
+    UnknownId(Id),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Truncated(len) => {
+                write!(
+                    f,
+                    "Message of {} bytes is shorter than the six-byte header",
+                    len
+                )
+            }
+            Error::InvalidLength {
+                id,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Message ID {:02X?} should be {} bytes long but is {} bytes long",
+                id, expected, actual
+            ),
+            Error::InvalidValue { id, value } => {
+                write!(
+                    f,
+                    "Message ID {:02X?} contains invalid value {:02X?}",
+                    id, value
+                )
+            }
+            Error::InvalidAddress {
+                id,
+                destination,
+                source,
+            } => write!(
+                f,
+                "Message ID {:02X?} has invalid destination {:#04X} or source {:#04X}",
+                id, destination, source
+            ),
+            Error::UnexpectedMessage(id) => write!(f, "Unexpected message ID {:02X?}", id),
+            Error::UnknownId(id) => write!(f, "Unknown message ID {:02X?}", id),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -9,16 +9,47 @@ modification, are permitted provided that the conditions of the LICENSE are met.
 */
 
 use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use crate::error::DeviceFault;
+use crate::messages::{HomeSettings, JogSettings, LimitSwitchSettings};
 
 #[derive(Debug)]
 pub enum Error {
     DeviceClosed,
+    DeviceDisconnected,
+    Fault(DeviceFault),
+    InvalidChannel(usize),
+    InvalidResponse(String),
+    NotApplied(Applied),
+    Timeout(Duration),
+    UnknownCommand([u8; 2]),
+}
+
+/// The settings reported by the device after a `SET` command, if they differ from the requested
+/// settings. For example, the device may clamp the maximum velocity to the limit of the stage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Applied {
+    /// The maximum velocity (mm/s) and acceleration (mm/s²).
+    Velocity { max_velocity: f64, acceleration: f64 },
+    Jog(JogSettings),
+    Home(HomeSettings),
+    LimitSwitch(LimitSwitchSettings),
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::DeviceClosed => write!(f, "Cannot send command to closed device"),
+            Error::DeviceDisconnected => write!(f, "Device is disconnected"),
+            Error::Fault(fault) => write!(f, "{}", fault),
+            Error::InvalidChannel(ch) => write!(f, "Invalid channel {}", ch),
+            Error::InvalidResponse(msg) => write!(f, "Invalid response from device : {}", msg),
+            Error::NotApplied(applied) => {
+                write!(f, "Device applied different settings : {:?}", applied)
+            }
+            Error::Timeout(t) => write!(f, "No response from device within {:?}", t),
+            Error::UnknownCommand(id) => write!(f, "Unknown command ID {:02X?}", id),
         }
     }
 }
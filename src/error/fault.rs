@@ -0,0 +1,70 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use std::fmt::{Display, Formatter};
+
+use crate::apt::{self, Message, bay};
+
+/// A fault reported asynchronously by a Thorlabs device.
+///
+/// Devices report faults that require user intervention (e.g. motor overheating, or a command
+/// which cannot be executed) using the `HW_RESPONSE` and `HW_RICHRESPONSE` messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceFault {
+    /// The numerical error code reported by the device.
+    pub code: u16,
+    /// The channel that the fault refers to, or [`None`] if the fault refers to the whole device.
+    pub channel: Option<usize>,
+    /// The ID of the message which caused the fault, if reported by the device.
+    pub message_id: Option<[u8; 2]>,
+    /// A description of the fault.
+    pub description: String,
+}
+
+impl DeviceFault {
+    /// Decodes a `HW_RESPONSE` or `HW_RICHRESPONSE` message into a [`DeviceFault`].
+    ///
+    /// Returns [`None`] if the message is not a valid fault report.
+    pub(crate) fn decode(message: &[u8]) -> Option<Self> {
+        let id = [message[0], message[1]];
+        if id != apt::id::HW_RESPONSE && id != apt::id::HW_RICHRESPONSE {
+            return None; // Avoid decoding every incoming message
+        }
+        let channel = bay(message);
+        match apt::decode(message).ok()? {
+            Message::HwResponse { code } => Some(Self {
+                code,
+                channel,
+                message_id: None,
+                description: String::from("Unexpected hardware event"),
+            }),
+            Message::HwRichResponse(response) => Some(Self {
+                code: response.code,
+                channel,
+                message_id: Some(response.message_id),
+                description: response.notes.trim().to_string(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl Display for DeviceFault {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Device fault {:#06X}", self.code)?;
+        if let Some(channel) = self.channel {
+            write!(f, " on channel {}", channel)?;
+        }
+        if let Some(id) = self.message_id {
+            write!(f, " caused by message ID {:02X?}", id)?;
+        }
+        write!(f, " : {}", self.description)
+    }
+}
@@ -8,7 +8,83 @@ Redistribution and use in source and binary forms, with or without
 modification, are permitted provided that the conditions of the LICENSE are met.
 */
 
+use std::fmt::{Display, Formatter};
+use std::io;
+
 /* ------------------------------------------------------------------------------ Public Modules */
 
+pub mod apt;
 pub mod cmd;
 pub mod sn;
+
+/* ----------------------------------------------------------------------------- Private Modules */
+
+mod fault;
+
+/* ------------------------------------------------------------------------------ Public Exports */
+
+pub use fault::DeviceFault;
+
+/// The error type returned by Thorlabs device functions.
+#[derive(Debug)]
+pub enum Error {
+    /// The serial number is invalid, or the device could not be found.
+    SerialNumber(sn::Error),
+    /// The command could not be completed.
+    Command(cmd::Error),
+    /// The [`Transport`][1] returned an I/O error.
+    ///
+    /// [1]: crate::traits::Transport
+    Io(io::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::SerialNumber(e) => write!(f, "{}", e),
+            Error::Command(e) => write!(f, "{}", e),
+            Error::Io(e) => write!(f, "I/O error : {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::SerialNumber(e) => Some(e),
+            Error::Command(e) => Some(e),
+            Error::Io(e) => Some(e),
+        }
+    }
+}
+
+impl From<sn::Error> for Error {
+    fn from(error: sn::Error) -> Self {
+        Error::SerialNumber(error)
+    }
+}
+
+impl From<cmd::Error> for Error {
+    fn from(error: cmd::Error) -> Self {
+        Error::Command(error)
+    }
+}
+
+impl From<apt::Error> for Error {
+    fn from(error: apt::Error) -> Self {
+        Error::Command(cmd::Error::InvalidResponse(error.to_string()))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+#[cfg(feature = "py")]
+impl From<Error> for pyo3::PyErr {
+    fn from(error: Error) -> Self {
+        pyo3::exceptions::PyException::new_err(error.to_string())
+    }
+}
@@ -19,7 +19,7 @@ pub enum Error {
     Invalid(Sn),
     Multiple(Sn),
     NotFound(Sn),
-    Unknown(DeviceInfo),
+    Unknown(Box<DeviceInfo>),
 }
 
 impl Display for Error {
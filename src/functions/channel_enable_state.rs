@@ -8,40 +8,49 @@ Redistribution and use in source and binary forms, with or without
 modification, are permitted provided that the conditions of the LICENSE are met.
 */
 
-use crate::devices::abort;
-use crate::messages::utils::short;
+use crate::apt::id::MOD_GET_CHANENABLESTATE;
+use crate::apt::{ChanEnableState, Message};
+use crate::devices::escalate;
+use crate::error::Error;
 use crate::traits::ThorlabsDevice;
 
-const SET_ENABLE_STATE: [u8; 2] = [0x10, 0x02];
-const REQ_ENABLE_STATE: [u8; 2] = [0x11, 0x02];
-const GET_ENABLE_STATE: [u8; 2] = [0x12, 0x02];
-
 #[doc = include_str!("../documentation/is_channel_enabled.md")]
-pub(crate) async fn is_channel_enabled<A, const CH: usize>(device: &A, channel: usize) -> bool
+pub(crate) async fn is_channel_enabled<A, const CH: usize>(
+    device: &A,
+    channel: usize,
+) -> Result<bool, Error>
 where
     A: ThorlabsDevice<CH>,
 {
     log::info!("{device} CHANNEL {channel} GET_ENABLE_STATE (requested)");
     // Subscribe to GET_ENABLE_STATE broadcast channel
-    let rx = device.inner().receiver(&GET_ENABLE_STATE, channel).await;
+    let rx = device
+        .inner()
+        .receiver(&MOD_GET_CHANENABLESTATE, channel)
+        .await?;
     if rx.is_new() {
         // No GET_ENABLE_STATE response pending from the device. Send new REQ command.
         log::info!("{device} CHANNEL {channel} GET_ENABLE_STATE (is new)");
-        let command = short(REQ_ENABLE_STATE, channel as u8, 0);
-        device.inner().send(command).await;
+        let command = device.inner().encode(
+            &Message::ModReqChanEnableState {
+                channel: channel as u8,
+            },
+            channel,
+        )?;
+        device.inner().send(command).await?;
     }
     // Wait for GET_ENABLE_STATE response
-    let response = rx.receive().await;
+    let response = device
+        .inner()
+        .receive(rx, &MOD_GET_CHANENABLESTATE, channel)
+        .await?;
     log::info!("{device} CHANNEL {channel} GET_ENABLE_STATE (responded)");
     // Parse the GET_ENABLE_STATE response
-    match response[3] {
-        0x01 => true,
-        0x02 => false,
-        _ => abort(format!(
-            "{device} CHANNEL {channel} GET_ENABLE_STATE (invalid response {:02X?})",
-            response[3]
-        )),
-    }
+    let state = ChanEnableState::decode(&response).map_err(|e| {
+        log::warn!("{device} CHANNEL {channel} GET_ENABLE_STATE (invalid) {e}");
+        escalate(e)
+    })?;
+    Ok(state.enabled)
 }
 
 #[doc = include_str!("../documentation/set_channel_enable_state.md")]
@@ -49,30 +58,50 @@ pub(crate) async fn set_channel_enabled<A, const CH: usize>(
     device: &A,
     channel: usize,
     enable: bool,
-) where
+) -> Result<(), Error>
+where
     A: ThorlabsDevice<CH>,
 {
     log::info!("{device} CHANNEL {channel} SET_ENABLE_STATE (requested)");
-    // Convert the boolean "enable" into a byte (Thorlabs APT Protocol)
-    let enable_byte: u8 = if enable { 0x01 } else { 0x02 };
+    let state = ChanEnableState {
+        channel: channel as u8,
+        enabled: enable,
+    };
     loop {
         // Subscribe to GET_ENABLE_STATE broadcast channel
-        let rx = device.inner().receiver(&GET_ENABLE_STATE, channel).await;
+        let rx = device
+            .inner()
+            .receiver(&MOD_GET_CHANENABLESTATE, channel)
+            .await?;
         if rx.is_new() {
             // No GET response pending from the device. Send new SET & REQ commands.
             log::info!("{device} CHANNEL {channel} SET_ENABLE_STATE (is new)");
-            let set = short(SET_ENABLE_STATE, channel as u8, enable_byte);
-            device.inner().send(set).await;
-            let req = short(REQ_ENABLE_STATE, channel as u8, 0);
-            device.inner().send(req).await;
+            let set = device
+                .inner()
+                .encode(&Message::ModSetChanEnableState(state), channel)?;
+            device.inner().send(set).await?;
+            let req = device.inner().encode(
+                &Message::ModReqChanEnableState {
+                    channel: channel as u8,
+                },
+                channel,
+            )?;
+            device.inner().send(req).await?;
         };
         // Wait for GET_ENABLE_STATE response
-        let response = rx.receive().await;
+        let response = device
+            .inner()
+            .receive(rx, &MOD_GET_CHANENABLESTATE, channel)
+            .await?;
         log::info!("{device} CHANNEL {channel} SET_ENABLE_STATE (responded)");
         // Parse the GET_ENABLE_STATE response
-        if response[3] == enable_byte {
+        if ChanEnableState::decode(&response)
+            .map_err(escalate)?
+            .enabled
+            == enable
+        {
             log::info!("{device} CHANNEL {channel} SET_ENABLE_STATE (success)");
-            break;
+            return Ok(());
         }
     }
 }
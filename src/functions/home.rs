@@ -8,27 +8,134 @@ Redistribution and use in source and binary forms, with or without
 modification, are permitted provided that the conditions of the LICENSE are met.
 */
 
-use crate::messages::utils::short;
-use crate::traits::ThorlabsDevice;
+use std::time::Duration;
 
-const HOME: [u8; 2] = [0x43, 0x04];
-const HOMED: [u8; 2] = [0x44, 0x04];
+use crate::apt::id::{MOT_GET_HOMEPARAMS, MOT_MOVE_HOMED};
+use crate::apt::{HomeParams, Message};
+use crate::devices::escalate;
+use crate::error::cmd::Applied;
+use crate::error::Error;
+use crate::functions::set_and_verify;
+use crate::messages::HomeSettings;
+use crate::traits::{ThorlabsDevice, UnitConversion, Units};
 
 #[doc = include_str!("../documentation/home.md")]
-pub(crate) async fn home<A, const CH: usize>(device: &A, channel: usize)
+pub(crate) async fn home<A, const CH: usize>(
+    device: &A,
+    channel: usize,
+    timeout: Option<Duration>,
+) -> Result<(), Error>
 where
     A: ThorlabsDevice<CH>,
 {
     log::info!("{device} CHANNEL {channel} HOME (requested)");
     // Subscribe to HOMED broadcast channel
-    let rx = device.inner().receiver(&HOMED, channel).await;
+    let rx = device.inner().receiver(&MOT_MOVE_HOMED, channel).await?;
     if rx.is_new() {
         // No HOMED response pending from the device. Send new HOME command.
         log::info!("{device} CHANNEL {channel} HOME (is new)");
-        let command = short(HOME, channel as u8, 0);
-        device.inner().send(command).await;
+        let command = device.inner().encode(
+            &Message::MotMoveHome {
+                channel: channel as u8,
+            },
+            channel,
+        )?;
+        device.inner().send(command).await?;
     }
-    // Wait for HOMED response
-    let _ = rx.receive().await; // No need to parse response
+    // Wait for HOMED response. No need to parse response
+    device
+        .inner()
+        .receive_timeout(rx, &MOT_MOVE_HOMED, channel, timeout)
+        .await?;
     log::info!("{device} CHANNEL {channel} HOME (success)");
+    Ok(())
+}
+
+#[doc = include_str!("../documentation/get_home_params.md")]
+pub(crate) async fn get_home_params<A, const CH: usize>(
+    device: &A,
+    channel: usize,
+) -> Result<HomeSettings, Error>
+where
+    A: ThorlabsDevice<CH> + UnitConversion,
+{
+    log::info!("{device} CHANNEL {channel} GET_HOMEPARAMS (requested)");
+    // Subscribe to GET_HOMEPARAMS broadcast channel
+    let rx = device
+        .inner()
+        .receiver(&MOT_GET_HOMEPARAMS, channel)
+        .await?;
+    if rx.is_new() {
+        // No GET_HOMEPARAMS response pending from the device. Send REQ_HOMEPARAMS command.
+        log::info!("{device} CHANNEL {channel} GET_HOMEPARAMS (is new)");
+        let command = device.inner().encode(
+            &Message::MotReqHomeParams {
+                channel: channel as u8,
+            },
+            channel,
+        )?;
+        device.inner().send(command).await?;
+    }
+    // Wait for GET_HOMEPARAMS response
+    let response = device
+        .inner()
+        .receive(rx, &MOT_GET_HOMEPARAMS, channel)
+        .await?;
+    log::info!("{device} CHANNEL {channel} GET_HOMEPARAMS (responded)");
+    // Parse the GET_HOMEPARAMS response
+    let params = HomeParams::decode(&response).map_err(escalate)?;
+    log::info!("{device} CHANNEL {channel} GET_HOMEPARAMS (success)");
+    Ok(decode(device, &params))
+}
+
+#[doc = include_str!("../documentation/set_home_params.md")]
+pub(crate) async fn set_home_params<A, const CH: usize>(
+    device: &A,
+    channel: usize,
+    settings: HomeSettings,
+) -> Result<(), Error>
+where
+    A: ThorlabsDevice<CH> + UnitConversion,
+{
+    log::info!("{device} CHANNEL {channel} SET_HOMEPARAMS (requested) {settings:?}");
+    let params = HomeParams {
+        channel: channel as u16,
+        direction: settings.direction,
+        limit_switch: settings.limit_switch,
+        velocity: i32::from_le_bytes(*A::velocity_from_f64(settings.velocity)),
+        offset: i32::from_le_bytes(*A::distance_from_f64(settings.offset)),
+    };
+    // The device may clamp or round the requested values
+    let applied = |actual: HomeParams| {
+        (actual != params).then_some(Applied::Home(decode(device, &actual)))
+    };
+    set_and_verify(
+        device,
+        channel,
+        Message::MotSetHomeParams(params),
+        Message::MotReqHomeParams {
+            channel: channel as u8,
+        },
+        &MOT_GET_HOMEPARAMS,
+        HomeParams::decode,
+        applied,
+    )
+    .await?;
+    log::info!("{device} CHANNEL {channel} SET_HOMEPARAMS (success)");
+    Ok(())
+}
+
+/// Converts the homing parameters from device units to real-world units.
+fn decode<A>(device: &A, params: &HomeParams) -> HomeSettings
+where
+    A: UnitConversion,
+{
+    let velocity = Units::velocity_from_slice(&params.velocity.to_le_bytes());
+    let offset = Units::distance_from_slice(&params.offset.to_le_bytes());
+    HomeSettings {
+        direction: params.direction,
+        limit_switch: params.limit_switch,
+        velocity: device.decode(velocity),
+        offset: device.decode(offset),
+    }
 }
@@ -8,18 +8,20 @@ Redistribution and use in source and binary forms, with or without
 modification, are permitted provided that the conditions of the LICENSE are met.
 */
 
-use crate::messages::utils::short;
+use crate::apt::Message;
+use crate::error::Error;
 use crate::traits::ThorlabsDevice;
 
-const IDENTIFY: [u8; 2] = [0x23, 0x02];
-
 #[doc = include_str!("../documentation/identify.md")]
-pub(crate) async fn identify<A, const CH: usize>(device: &A, channel: u8)
+pub(crate) async fn identify<A, const CH: usize>(device: &A, channel: u8) -> Result<(), Error>
 where
     A: ThorlabsDevice<CH>,
 {
     log::info!("{device} CHANNEL {channel} IDENTIFY (requested)");
-    let command = short(IDENTIFY, channel, 0);
-    device.inner().send(command).await;
+    let command = device
+        .inner()
+        .encode(&Message::ModIdentify { channel }, channel as usize)?;
+    device.inner().send(command).await?;
     log::info!("{device} CHANNEL {channel} IDENTIFY (success)");
+    Ok(())
 }
@@ -0,0 +1,164 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use std::time::Duration;
+
+use crate::apt::id::{MOT_GET_JOGPARAMS, MOT_MOVE_COMPLETED, MOT_MOVE_STOPPED};
+use crate::apt::{Direction, JogParams, Message};
+use crate::devices::escalate;
+use crate::error::cmd::Applied;
+use crate::error::Error;
+use crate::functions::set_and_verify;
+use crate::messages::JogSettings;
+use crate::traits::{ThorlabsDevice, UnitConversion, Units};
+
+#[doc = include_str!("../documentation/get_jog_params.md")]
+pub(crate) async fn get_jog_params<A, const CH: usize>(
+    device: &A,
+    channel: usize,
+) -> Result<JogSettings, Error>
+where
+    A: ThorlabsDevice<CH> + UnitConversion,
+{
+    log::info!("{device} CHANNEL {channel} GET_JOGPARAMS (requested)");
+    // Subscribe to GET_JOGPARAMS broadcast channel
+    let rx = device.inner().receiver(&MOT_GET_JOGPARAMS, channel).await?;
+    if rx.is_new() {
+        // No GET_JOGPARAMS response pending from the device. Send REQ_JOGPARAMS command.
+        log::info!("{device} CHANNEL {channel} GET_JOGPARAMS (is new)");
+        let command = device.inner().encode(
+            &Message::MotReqJogParams {
+                channel: channel as u8,
+            },
+            channel,
+        )?;
+        device.inner().send(command).await?;
+    }
+    // Wait for GET_JOGPARAMS response
+    let response = device
+        .inner()
+        .receive(rx, &MOT_GET_JOGPARAMS, channel)
+        .await?;
+    log::info!("{device} CHANNEL {channel} GET_JOGPARAMS (responded)");
+    // Parse the GET_JOGPARAMS response
+    let params = JogParams::decode(&response).map_err(escalate)?;
+    log::info!("{device} CHANNEL {channel} GET_JOGPARAMS (success)");
+    Ok(decode(device, &params))
+}
+
+#[doc = include_str!("../documentation/set_jog_params.md")]
+pub(crate) async fn set_jog_params<A, const CH: usize>(
+    device: &A,
+    channel: usize,
+    settings: JogSettings,
+) -> Result<(), Error>
+where
+    A: ThorlabsDevice<CH> + UnitConversion,
+{
+    log::info!("{device} CHANNEL {channel} SET_JOGPARAMS (requested) {settings:?}");
+    let params = JogParams {
+        channel: channel as u16,
+        mode: settings.mode,
+        step_size: i32::from_le_bytes(*A::distance_from_f64(settings.step_size)),
+        min_velocity: 0, // Currently unused by Thorlabs devices
+        acceleration: i32::from_le_bytes(*A::acceleration_from_f64(settings.acceleration)),
+        max_velocity: i32::from_le_bytes(*A::velocity_from_f64(settings.velocity)),
+        stop_mode: settings.stop_mode,
+    };
+    // The device may clamp or round the requested values. The minimum velocity is not compared.
+    let applied = |actual: JogParams| {
+        let applied = decode(device, &actual);
+        let actual = JogParams {
+            min_velocity: params.min_velocity,
+            ..actual
+        };
+        (actual != params).then_some(Applied::Jog(applied))
+    };
+    set_and_verify(
+        device,
+        channel,
+        Message::MotSetJogParams(params),
+        Message::MotReqJogParams {
+            channel: channel as u8,
+        },
+        &MOT_GET_JOGPARAMS,
+        JogParams::decode,
+        applied,
+    )
+    .await?;
+    log::info!("{device} CHANNEL {channel} SET_JOGPARAMS (success)");
+    Ok(())
+}
+
+/// Converts the jog parameters from device units to real-world units.
+fn decode<A>(device: &A, params: &JogParams) -> JogSettings
+where
+    A: UnitConversion,
+{
+    let step_size = Units::distance_from_slice(&params.step_size.to_le_bytes());
+    let velocity = Units::velocity_from_slice(&params.max_velocity.to_le_bytes());
+    let acceleration = Units::acceleration_from_slice(&params.acceleration.to_le_bytes());
+    JogSettings {
+        mode: params.mode,
+        step_size: device.decode(step_size),
+        velocity: device.decode(velocity),
+        acceleration: device.decode(acceleration),
+        stop_mode: params.stop_mode,
+    }
+}
+
+#[doc = include_str!("../documentation/jog.md")]
+pub(crate) async fn jog<A, const CH: usize>(
+    device: &A,
+    channel: usize,
+    direction: Direction,
+    timeout: Option<Duration>,
+) -> Result<(), Error>
+where
+    A: ThorlabsDevice<CH>,
+{
+    log::info!("{device} CHANNEL {channel} MOVE_JOG {direction:?} (requested)");
+    // Subscribe to MOVE_COMPLETED broadcast channel
+    let completed = device
+        .inner()
+        .new_receiver(&MOT_MOVE_COMPLETED, channel)
+        .await?;
+    // Subscribe to STOPPED broadcast channel. A jog move which is interrupted using STOP does not
+    // complete.
+    let stopped = device.inner().receiver(&MOT_MOVE_STOPPED, channel).await?;
+    let command = device.inner().encode(
+        &Message::MotMoveJog {
+            channel: channel as u8,
+            direction,
+        },
+        channel,
+    )?;
+    device.inner().send(command).await?;
+    // Wait for MOVE_COMPLETED or STOPPED response. No need to parse response
+    let result = smol::future::or(
+        device
+            .inner()
+            .receive_timeout(completed, &MOT_MOVE_COMPLETED, channel, timeout),
+        device
+            .inner()
+            .receive_timeout(stopped, &MOT_MOVE_STOPPED, channel, None),
+    )
+    .await;
+    // Remove the broadcast channel which did not respond, unless another function is waiting
+    device.inner().release(&MOT_MOVE_COMPLETED, channel).await?;
+    device.inner().release(&MOT_MOVE_STOPPED, channel).await?;
+    let response = result?;
+    if response[..2] == MOT_MOVE_STOPPED {
+        log::info!("{device} CHANNEL {channel} MOVE_JOG {direction:?} (stopped)");
+    } else {
+        log::info!("{device} CHANNEL {channel} MOVE_JOG {direction:?} (success)");
+    }
+    Ok(())
+}
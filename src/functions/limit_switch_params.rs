@@ -0,0 +1,114 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use crate::apt::id::MOT_GET_LIMSWITCHPARAMS;
+use crate::apt::{LimSwitchParams, Message};
+use crate::devices::escalate;
+use crate::error::cmd::Applied;
+use crate::error::Error;
+use crate::functions::set_and_verify;
+use crate::messages::LimitSwitchSettings;
+use crate::traits::{ThorlabsDevice, UnitConversion, Units};
+
+#[doc = include_str!("../documentation/get_limit_switch_params.md")]
+pub(crate) async fn get_limit_switch_params<A, const CH: usize>(
+    device: &A,
+    channel: usize,
+) -> Result<LimitSwitchSettings, Error>
+where
+    A: ThorlabsDevice<CH> + UnitConversion,
+{
+    log::info!("{device} CHANNEL {channel} GET_LIMSWITCHPARAMS (requested)");
+    // Subscribe to GET_LIMSWITCHPARAMS broadcast channel
+    let rx = device
+        .inner()
+        .receiver(&MOT_GET_LIMSWITCHPARAMS, channel)
+        .await?;
+    if rx.is_new() {
+        // No GET_LIMSWITCHPARAMS response pending from the device. Send REQ_LIMSWITCHPARAMS
+        // command.
+        log::info!("{device} CHANNEL {channel} GET_LIMSWITCHPARAMS (is new)");
+        let command = device.inner().encode(
+            &Message::MotReqLimSwitchParams {
+                channel: channel as u8,
+            },
+            channel,
+        )?;
+        device.inner().send(command).await?;
+    }
+    // Wait for GET_LIMSWITCHPARAMS response
+    let response = device
+        .inner()
+        .receive(rx, &MOT_GET_LIMSWITCHPARAMS, channel)
+        .await?;
+    log::info!("{device} CHANNEL {channel} GET_LIMSWITCHPARAMS (responded)");
+    // Parse the GET_LIMSWITCHPARAMS response
+    let params = LimSwitchParams::decode(&response).map_err(escalate)?;
+    log::info!("{device} CHANNEL {channel} GET_LIMSWITCHPARAMS (success)");
+    Ok(decode(device, &params))
+}
+
+#[doc = include_str!("../documentation/set_limit_switch_params.md")]
+pub(crate) async fn set_limit_switch_params<A, const CH: usize>(
+    device: &A,
+    channel: usize,
+    settings: LimitSwitchSettings,
+) -> Result<(), Error>
+where
+    A: ThorlabsDevice<CH> + UnitConversion,
+{
+    log::info!("{device} CHANNEL {channel} SET_LIMSWITCHPARAMS (requested) {settings:?}");
+    let params = LimSwitchParams {
+        channel: channel as u16,
+        cw_hard_limit: settings.cw_hard_limit,
+        ccw_hard_limit: settings.ccw_hard_limit,
+        swapped: settings.swapped,
+        cw_soft_limit: i32::from_le_bytes(*A::distance_from_f64(settings.cw_soft_limit)),
+        ccw_soft_limit: i32::from_le_bytes(*A::distance_from_f64(settings.ccw_soft_limit)),
+        soft_limit_mode: settings.soft_limit_mode,
+        rotation: settings.rotation,
+    };
+    // The device may clamp or round the requested values
+    let applied = |actual: LimSwitchParams| {
+        (actual != params).then_some(Applied::LimitSwitch(decode(device, &actual)))
+    };
+    set_and_verify(
+        device,
+        channel,
+        Message::MotSetLimSwitchParams(params),
+        Message::MotReqLimSwitchParams {
+            channel: channel as u8,
+        },
+        &MOT_GET_LIMSWITCHPARAMS,
+        LimSwitchParams::decode,
+        applied,
+    )
+    .await?;
+    log::info!("{device} CHANNEL {channel} SET_LIMSWITCHPARAMS (success)");
+    Ok(())
+}
+
+/// Converts the limit switch parameters from device units to real-world units.
+fn decode<A>(device: &A, params: &LimSwitchParams) -> LimitSwitchSettings
+where
+    A: UnitConversion,
+{
+    let cw_soft_limit = Units::distance_from_slice(&params.cw_soft_limit.to_le_bytes());
+    let ccw_soft_limit = Units::distance_from_slice(&params.ccw_soft_limit.to_le_bytes());
+    LimitSwitchSettings {
+        cw_hard_limit: params.cw_hard_limit,
+        ccw_hard_limit: params.ccw_hard_limit,
+        swapped: params.swapped,
+        cw_soft_limit: device.decode(cw_soft_limit),
+        ccw_soft_limit: device.decode(ccw_soft_limit),
+        soft_limit_mode: params.soft_limit_mode,
+        rotation: params.rotation,
+    }
+}
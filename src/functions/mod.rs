@@ -13,21 +13,31 @@ modification, are permitted provided that the conditions of the LICENSE are met.
 mod channel_enable_state;
 mod home;
 mod identify;
+mod jog;
+mod limit_switch_params;
 mod move_absolute;
 mod move_relative;
+mod raw;
+mod set_and_verify;
 mod status_bits;
 mod status_update;
 mod stop;
 mod update_messages;
+mod velocity_params;
 
 /* ----------------------------------------------------------------------------- Private Exports */
 
 pub(crate) use channel_enable_state::*;
 pub(crate) use home::*;
 pub(crate) use identify::*;
+pub(crate) use jog::*;
+pub(crate) use limit_switch_params::*;
 pub(crate) use move_absolute::*;
 pub(crate) use move_relative::*;
+pub(crate) use raw::*;
+pub(crate) use set_and_verify::*;
 pub(crate) use status_bits::*;
 pub(crate) use status_update::*;
 pub(crate) use stop::*;
 pub(crate) use update_messages::*;
+pub(crate) use velocity_params::*;
@@ -8,61 +8,93 @@ Redistribution and use in source and binary forms, with or without
 modification, are permitted provided that the conditions of the LICENSE are met.
 */
 
-use crate::messages::utils::{long, short};
-use crate::traits::{ThorlabsDevice, UnitConversion, Units};
+use std::time::{Duration, Instant};
 
-const MOVE_ABSOLUTE: [u8; 2] = [0x53, 0x04];
-const MOVE_COMPLETED: [u8; 2] = [0x64, 0x04];
+use crate::apt::id::MOT_MOVE_COMPLETED;
+use crate::apt::{Message, Move, UStatus};
+use crate::devices::escalate;
+use crate::error::Error;
+use crate::traits::{ThorlabsDevice, UnitConversion, Units};
 
 #[doc = include_str!("../documentation/move_absolute.md")]
-pub(crate) async fn move_absolute<A, const CH: usize>(device: &A, channel: usize, position: f64)
+pub(crate) async fn move_absolute<A, const CH: usize>(
+    device: &A,
+    channel: usize,
+    position: f64,
+    timeout: Option<Duration>,
+) -> Result<(), Error>
 where
     A: ThorlabsDevice<CH> + UnitConversion,
 {
     log::info!("{device} CHANNEL {channel} MOVE_ABSOLUTE {position} (requested)");
+    // The timeout applies to the whole move, which may require several MOVE_COMPLETED responses
+    let deadline = timeout.map(|t| Instant::now() + t);
     loop {
         // Subscribe to MOVE_COMPLETED broadcast channel
-        let rx = device.inner().receiver(&MOVE_COMPLETED, channel).await;
+        let rx = device
+            .inner()
+            .receiver(&MOT_MOVE_COMPLETED, channel)
+            .await?;
         if rx.is_new() {
             // No MOVE_COMPLETED response pending from the device. Send MOVE_ABSOLUTE command.
             log::info!("{device} CHANNEL {channel} MOVE_ABSOLUTE {position} (is new)");
-            let command = {
-                let mut data: Vec<u8> = Vec::with_capacity(6);
-                data.extend((channel as u16).to_le_bytes());
-                data.extend(A::distance_from_f64(position));
-                long(MOVE_ABSOLUTE, &data)
-            };
-            device.inner().send(command).await;
+            let command = device.inner().encode(
+                &Message::MotMoveAbsolute(Move {
+                    channel: channel as u16,
+                    distance: i32::from_le_bytes(*A::distance_from_f64(position)),
+                }),
+                channel,
+            )?;
+            device.inner().send(command).await?;
         }
         // Wait for MOVE_COMPLETED response
-        let response = rx.receive().await;
+        let remaining = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+        let response = device
+            .inner()
+            .receive_timeout(rx, &MOT_MOVE_COMPLETED, channel, remaining)
+            .await?;
         log::info!("{device} CHANNEL {channel} MOVE_ABSOLUTE {position} (responded)");
-        // Parse the MOVE_COMPLETED response
-        let p = device.decode(Units::distance_from_slice(&response[8..12]));
-        if Units::approx(p, position) {
+        // Compare the MOVE_COMPLETED position with the requested position in device units
+        let status = UStatus::decode(&response).map_err(escalate)?;
+        if *A::distance_from_f64(position) == status.position.to_le_bytes() {
             log::info!("{device} CHANNEL {channel} MOVE_ABSOLUTE {position} (success)");
-            return;
+            return Ok(());
         }
     }
 }
 
 #[doc = include_str!("../documentation/move_absolute_from_params.md")]
-pub(crate) async fn move_absolute_from_params<A, const CH: usize>(device: &A, channel: usize) -> f64
+pub(crate) async fn move_absolute_from_params<A, const CH: usize>(
+    device: &A,
+    channel: usize,
+) -> Result<f64, Error>
 where
     A: ThorlabsDevice<CH> + UnitConversion,
 {
     log::info!("{device} CHANNEL {channel} MOVE_ABSOLUTE_FROM_PARAMS (requested)");
     // Subscribe to MOVE_COMPLETED broadcast channel
-    let rx = device.inner().new_receiver(&MOVE_COMPLETED, channel).await;
+    let rx = device
+        .inner()
+        .new_receiver(&MOT_MOVE_COMPLETED, channel)
+        .await?;
     {
         // No MOVE_COMPLETED response pending from the device. Send MOVE_ABSOLUTE command.
         log::info!("{device} CHANNEL {channel} MOVE_ABSOLUTE_FROM_PARAMS (is new)");
-        let command = short(MOVE_ABSOLUTE, channel as u8, 0);
-        device.inner().send(command).await;
+        let command = device.inner().encode(
+            &Message::MotMoveAbsoluteFromParams {
+                channel: channel as u8,
+            },
+            channel,
+        )?;
+        device.inner().send(command).await?;
     }
     // Wait for MOVE_COMPLETED response
-    let response = rx.receive().await;
+    let response = device
+        .inner()
+        .receive(rx, &MOT_MOVE_COMPLETED, channel)
+        .await?;
     log::info!("{device} CHANNEL {channel} MOVE_ABSOLUTE_FROM_PARAMS (responded)");
     // Return the new position
-    device.decode(Units::distance_from_slice(&response[8..12]))
+    let status = UStatus::decode(&response).map_err(escalate)?;
+    Ok(device.decode(Units::distance_from_slice(&status.position.to_le_bytes())))
 }
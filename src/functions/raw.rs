@@ -0,0 +1,62 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use std::sync::Arc;
+
+use crate::apt::HOST;
+use crate::error::Error;
+use crate::traits::ThorlabsDevice;
+
+#[doc = include_str!("../documentation/send_raw.md")]
+pub(crate) async fn send_raw<A, const CH: usize>(device: &A, bytes: Vec<u8>) -> Result<(), Error>
+where
+    A: ThorlabsDevice<CH>,
+{
+    log::info!("{device} SEND_RAW {bytes:02X?} (requested)");
+    device.inner().send(bytes).await?;
+    log::info!("{device} SEND_RAW (success)");
+    Ok(())
+}
+
+#[doc = include_str!("../documentation/request_raw.md")]
+pub(crate) async fn request_raw<A, const CH: usize>(
+    device: &A,
+    req_id: [u8; 2],
+    get_id: [u8; 2],
+    channel: usize,
+    payload: &[u8],
+) -> Result<Arc<[u8]>, Error>
+where
+    A: ThorlabsDevice<CH>,
+{
+    log::info!("{device} CHANNEL {channel} REQUEST_RAW {req_id:02X?} (requested)");
+    // Subscribe to the get_id broadcast channel
+    let rx = device.inner().receiver(&get_id, channel).await?;
+    if rx.is_new() {
+        // No get_id response pending from the device. Send new req_id command.
+        log::info!("{device} CHANNEL {channel} REQUEST_RAW {req_id:02X?} (is new)");
+        let destination = device.inner().destination(channel)?;
+        let command = match payload.is_empty() {
+            true => vec![req_id[0], req_id[1], channel as u8, 0, destination, HOST],
+            false => [
+                req_id.as_slice(),
+                &(payload.len() as u16).to_le_bytes(),
+                &[destination | 0x80, HOST],
+                payload,
+            ]
+            .concat(),
+        };
+        device.inner().send(command).await?;
+    }
+    // Wait for get_id response. The caller parses the response.
+    let response = device.inner().receive(rx, &get_id, channel).await?;
+    log::info!("{device} CHANNEL {channel} REQUEST_RAW {req_id:02X?} (success)");
+    Ok(response)
+}
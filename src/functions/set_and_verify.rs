@@ -0,0 +1,52 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use crate::apt::Message;
+use crate::devices::escalate;
+use crate::error::cmd::Applied;
+use crate::error::{Error, apt, cmd};
+use crate::traits::ThorlabsDevice;
+
+/// Sends a `SET` command, then requests the parameters back from the device once.
+///
+/// The `GET` response is decoded using `decode`, then passed to `applied`. If `applied` returns
+/// [`Some`], the device applied different parameters from those requested (for example, by
+/// clamping a velocity), and [`cmd::Error::NotApplied`] is returned.
+pub(crate) async fn set_and_verify<A, P, F, const CH: usize>(
+    device: &A,
+    channel: usize,
+    set: Message,
+    req: Message,
+    get: &[u8],
+    decode: fn(&[u8]) -> Result<P, apt::Error>,
+    applied: F,
+) -> Result<(), Error>
+where
+    A: ThorlabsDevice<CH>,
+    F: FnOnce(P) -> Option<Applied>,
+{
+    let id = set.id();
+    // Wait for any pending GET response, so that the next response follows the SET
+    let rx = device.inner().new_receiver(get, channel).await?;
+    let set = device.inner().encode(&set, channel)?;
+    device.inner().send(set).await?;
+    let req = device.inner().encode(&req, channel)?;
+    device.inner().send(req).await?;
+    // Wait for GET response
+    let response = device.inner().receive(rx, get, channel).await?;
+    log::info!("{device} CHANNEL {channel} SET {id:02X?} (responded)");
+    // Parse the GET response
+    let actual = decode(&response).map_err(escalate)?;
+    if let Some(applied) = applied(actual) {
+        log::warn!("{device} CHANNEL {channel} SET {id:02X?} (not applied) {applied:?}");
+        return Err(escalate(cmd::Error::NotApplied(applied)));
+    }
+    Ok(())
+}
@@ -8,29 +8,66 @@ Redistribution and use in source and binary forms, with or without
 modification, are permitted provided that the conditions of the LICENSE are met.
 */
 
-use crate::ThorlabsDevice;
-use crate::messages::utils::short;
+use std::time::Duration;
 
-const REQ_STATUS_BITS: [u8; 2] = [0x29, 0x04];
-const GET_STATUS_BITS: [u8; 2] = [0x2A, 0x04];
+use crate::ThorlabsDevice;
+use crate::apt::id::MOT_GET_STATUSBITS;
+use crate::apt::{Message, StatusBits};
+use crate::devices::escalate;
+use crate::error::Error;
 
 #[doc = include_str!("../documentation/get_status_bits.md")]
-pub(crate) async fn get_status_bits<A, const CH: usize>(device: &A, channel: usize) -> u32
+pub(crate) async fn get_status_bits<A, const CH: usize>(
+    device: &A,
+    channel: usize,
+) -> Result<u32, Error>
 where
     A: ThorlabsDevice<CH>,
 {
     log::info!("{device} CHANNEL {channel} GET_STATUS_BITS (requested)");
     // Subscribe to GET_STATUS_BITS broadcast channel
-    let rx = device.inner().receiver(&GET_STATUS_BITS, channel).await;
+    let rx = device
+        .inner()
+        .receiver(&MOT_GET_STATUSBITS, channel)
+        .await?;
     if rx.is_new() {
         // No GET_STATUS_BITS response pending from the device. Send REQ_STATUS_BITS command.
         log::info!("{device} CHANNEL {channel} GET_STATUS_BITS (is new)");
-        let command = short(REQ_STATUS_BITS, channel as u8, 0);
-        device.inner().send(command).await;
+        let command = device.inner().encode(
+            &Message::MotReqStatusBits {
+                channel: channel as u8,
+            },
+            channel,
+        )?;
+        device.inner().send(command).await?;
     }
     // Wait for GET_STATUS_BITS response
-    let response = rx.receive().await;
+    let response = device
+        .inner()
+        .receive(rx, &MOT_GET_STATUSBITS, channel)
+        .await?;
     log::info!("{device} CHANNEL {channel} GET_STATUS_BITS (success)");
-    // Return little-endian status bits as u32
-    u32::from_le_bytes([response[8], response[9], response[10], response[11]])
+    // Parse the GET_STATUS_BITS response
+    Ok(StatusBits::decode(&response).map_err(escalate)?.status_bits)
+}
+
+#[doc = include_str!("../documentation/get_status_bits.md")]
+///
+/// Returns the cached status bits if they were received within `max_age`. Otherwise, requests the
+/// status bits from the device.
+pub(crate) async fn get_status_bits_cached<A, const CH: usize>(
+    device: &A,
+    channel: usize,
+    max_age: Duration,
+) -> Result<u32, Error>
+where
+    A: ThorlabsDevice<CH>,
+{
+    match device.inner().cached(channel, max_age).await {
+        Some(status) => {
+            log::info!("{device} CHANNEL {channel} GET_STATUS_BITS (cached)");
+            Ok(status.status_bits)
+        }
+        None => get_status_bits(device, channel).await,
+    }
 }
@@ -8,36 +8,121 @@ Redistribution and use in source and binary forms, with or without
 modification, are permitted provided that the conditions of the LICENSE are met.
 */
 
-use crate::messages::utils::short;
-use crate::traits::{ThorlabsDevice, UnitConversion, Units};
+use std::time::Duration;
+
+use async_broadcast::RecvError;
+use smol::stream::Stream;
 
-const REQ_U_STATUS_UPDATE: [u8; 2] = [0x90, 0x04];
-const GET_U_STATUS_UPDATE: [u8; 2] = [0x91, 0x04];
+use crate::apt::id::MOT_GET_USTATUSUPDATE;
+use crate::apt::{Message, UStatus};
+use crate::devices::escalate;
+use crate::error::Error;
+use crate::messages::StatusUpdate;
+use crate::traits::{ThorlabsDevice, UnitConversion, Units};
 
 #[doc = include_str!("../documentation/get_status.md")]
 pub(crate) async fn get_u_status_update<A, const CH: usize>(
     device: &A,
     channel: usize,
-) -> (f64, f64, u32)
+) -> Result<(f64, f64, u32), Error>
 where
     A: ThorlabsDevice<CH> + UnitConversion,
 {
     log::info!("{device} CHANNEL {channel} U_STATUS_UPDATE (requested)");
     // Subscribe to GET_U_STATUS_UPDATE broadcast channel
-    let rx = device.inner().receiver(&GET_U_STATUS_UPDATE, channel).await;
+    let rx = device
+        .inner()
+        .receiver(&MOT_GET_USTATUSUPDATE, channel)
+        .await?;
     if rx.is_new() {
         // No GET_U_STATUS_UPDATE response pending from the device. Send REQ_U_STATUS_UPDATE.
         log::info!("{device} CHANNEL {channel} U_STATUS_UPDATE (is new)");
-        let command = short(REQ_U_STATUS_UPDATE, channel as u8, 0);
-        device.inner().send(command).await;
+        let command = device.inner().encode(
+            &Message::MotReqUStatusUpdate {
+                channel: channel as u8,
+            },
+            channel,
+        )?;
+        device.inner().send(command).await?;
     }
     // Wait for GET_U_STATUS_UPDATE response
-    let response = rx.receive().await;
+    let response = device
+        .inner()
+        .receive(rx, &MOT_GET_USTATUSUPDATE, channel)
+        .await?;
     log::info!("{device} CHANNEL {channel} U_STATUS_UPDATE (responded)");
     // Parse the GET_U_STATUS_UPDATE response
-    let position = device.decode(Units::distance_from_slice(&response[8..12]));
-    let velocity = device.decode(Units::velocity_from_slice(&response[12..14]));
-    let bits = u32::from_le_bytes([response[16], response[17], response[18], response[19]]);
+    let status = UStatus::decode(&response).map_err(escalate)?;
     log::info!("{device} CHANNEL {channel} U_STATUS_UPDATE (success)");
-    (position, velocity, bits)
+    Ok(convert(device, &status))
+}
+
+#[doc = include_str!("../documentation/get_status.md")]
+///
+/// Returns the cached status if it was received within `max_age`. Otherwise, requests a new
+/// status update from the device.
+pub(crate) async fn get_u_status_update_cached<A, const CH: usize>(
+    device: &A,
+    channel: usize,
+    max_age: Duration,
+) -> Result<(f64, f64, u32), Error>
+where
+    A: ThorlabsDevice<CH> + UnitConversion,
+{
+    match device.inner().cached(channel, max_age).await {
+        Some(status) => {
+            log::info!("{device} CHANNEL {channel} U_STATUS_UPDATE (cached)");
+            Ok(convert(device, &status))
+        }
+        None => get_u_status_update(device, channel).await,
+    }
+}
+
+/// Converts a [`UStatus`] into position (mm), velocity (mm/s), and status bits.
+fn convert<A>(device: &A, status: &UStatus) -> (f64, f64, u32)
+where
+    A: UnitConversion,
+{
+    let position = device.decode(Units::distance_from_slice(&status.position.to_le_bytes()));
+    let velocity = device.decode(Units::velocity_from_slice(&status.velocity.to_le_bytes()));
+    (position, velocity, status.status_bits)
+}
+
+#[doc = include_str!("../documentation/status_stream.md")]
+pub(crate) async fn status_stream<A, const CH: usize>(
+    device: &A,
+) -> impl Stream<Item = StatusUpdate> + Send + Unpin + use<A, CH>
+where
+    A: ThorlabsDevice<CH> + UnitConversion + Clone + 'static,
+{
+    log::info!("{device} STATUS_STREAM (requested)");
+    let rx = device.inner().updates().await;
+    let device = device.clone(); // Inexpensive Arc Clone
+    Box::pin(smol::stream::unfold(
+        (rx, device),
+        |(mut rx, device)| async move {
+            let mut missed = 0;
+            loop {
+                match rx.recv_direct().await {
+                    Ok((timestamp, channel, status)) => {
+                        let (position, velocity, status_bits) = convert(&device, &status);
+                        let update = StatusUpdate {
+                            channel,
+                            position,
+                            velocity,
+                            status_bits,
+                            timestamp,
+                            missed,
+                        };
+                        return Some((update, (rx, device)));
+                    }
+                    Err(RecvError::Overflowed(n)) => {
+                        log::warn!("{device} STATUS_STREAM (lagged) MISSED {n}");
+                        missed += n;
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        },
+    ))
 }
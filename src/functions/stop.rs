@@ -8,46 +8,63 @@ Redistribution and use in source and binary forms, with or without
 modification, are permitted provided that the conditions of the LICENSE are met.
 */
 
-use crate::messages::utils::short;
+use crate::apt::id::MOT_MOVE_STOPPED;
+use crate::apt::{Message, StopMode};
+use crate::error::Error;
 use crate::traits::ThorlabsDevice;
 
-const STOP: [u8; 2] = [0x65, 0x04];
-const STOPPED: [u8; 2] = [0x66, 0x04];
-
 #[doc = include_str!("../documentation/stop.md")]
-pub(crate) async fn stop<A, const CH: usize>(device: &A, channel: usize)
+pub(crate) async fn stop<A, const CH: usize>(device: &A, channel: usize) -> Result<(), Error>
 where
     A: ThorlabsDevice<CH>,
 {
     log::info!("{device} CHANNEL {channel} STOP (requested)");
     // Subscribe to STOPPED broadcast channel
-    let rx = device.inner().receiver(&STOPPED, channel).await;
-    if rx.is_new() {
-        // No STOPPED response pending from the device. Send STOP command.
-        log::info!("{device} CHANNEL {channel} STOP (is new)");
-        let command = short(STOP, channel as u8, 0x02);
-        device.inner().send(command).await;
-    }
+    let rx = device.inner().receiver(&MOT_MOVE_STOPPED, channel).await?;
+    // Always send STOP. A pending STOPPED response may belong to a jog move, which is waiting
+    // for the device to stop rather than stopping it.
+    let command = device.inner().encode(
+        &Message::MotMoveStop {
+            channel: channel as u8,
+            mode: StopMode::Profiled,
+        },
+        channel,
+    )?;
+    device.inner().send(command).await?;
     // Wait for STOPPED response
-    let _ = rx.receive().await; // No need to parse response
+    device
+        .inner()
+        .receive(rx, &MOT_MOVE_STOPPED, channel)
+        .await?; // No need to parse response
     log::info!("{device} CHANNEL {channel} STOP (success)");
+    Ok(())
 }
 
 #[doc = include_str!("../documentation/estop.md")]
-pub(crate) async fn estop<A, const CH: usize>(device: &A, channel: usize)
+pub(crate) async fn estop<A, const CH: usize>(device: &A, channel: usize) -> Result<(), Error>
 where
     A: ThorlabsDevice<CH>,
 {
     log::info!("{device} CHANNEL {channel} ESTOP (requested)");
     // Subscribe to STOPPED broadcast channel
-    let rx = device.inner().receiver(&STOPPED, channel).await;
+    let rx = device.inner().receiver(&MOT_MOVE_STOPPED, channel).await?;
     if rx.is_new() {
         // No STOPPED response pending from the device. Send ESTOP command.
         log::info!("{device} CHANNEL {channel} ESTOP (is new)");
-        let command = short(STOP, channel as u8, 0x01);
-        device.inner().send(command).await;
+        let command = device.inner().encode(
+            &Message::MotMoveStop {
+                channel: channel as u8,
+                mode: StopMode::Immediate,
+            },
+            channel,
+        )?;
+        device.inner().send(command).await?;
     }
     // Wait for STOPPED response
-    let _ = rx.receive().await; // No need to parse response
+    device
+        .inner()
+        .receive(rx, &MOT_MOVE_STOPPED, channel)
+        .await?; // No need to parse response
     log::info!("{device} CHANNEL {channel} ESTOP (success)");
+    Ok(())
 }
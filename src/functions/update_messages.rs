@@ -8,30 +8,33 @@ Redistribution and use in source and binary forms, with or without
 modification, are permitted provided that the conditions of the LICENSE are met.
 */
 
-use crate::messages::utils::short;
+use crate::apt::Message;
+use crate::error::Error;
 use crate::traits::ThorlabsDevice;
 
-const START_UPDATE_MESSAGES: [u8; 2] = [0x11, 0x00];
-const STOP_UPDATE_MESSAGES: [u8; 2] = [0x12, 0x00];
-
 #[doc = include_str!("../documentation/start_update_messages.md")]
-pub(crate) async fn start_update_messages<A, const CH: usize>(device: &A)
+pub(crate) async fn start_update_messages<A, const CH: usize>(device: &A) -> Result<(), Error>
 where
     A: ThorlabsDevice<CH>,
 {
     log::info!("{device} START_UPDATE_MESSAGES (requested)");
-    let command = short(START_UPDATE_MESSAGES, 0, 0);
-    device.inner().send(command).await;
+    let command = device.inner().encode(&Message::HwStartUpdateMsgs, 0)?;
+    device.inner().send(command).await?;
+    // The device stops sending update messages unless they are acknowledged
+    device.inner().set_update_messages(true).await;
     log::info!("{device} START_UPDATE_MESSAGES (success)");
+    Ok(())
 }
 
 #[doc = include_str!("../documentation/stop_update_messages.md")]
-pub(crate) async fn stop_update_messages<A, const CH: usize>(device: &A)
+pub(crate) async fn stop_update_messages<A, const CH: usize>(device: &A) -> Result<(), Error>
 where
     A: ThorlabsDevice<CH>,
 {
     log::info!("{device} STOP_UPDATE_MESSAGES (requested)");
-    let command = short(STOP_UPDATE_MESSAGES, 0, 0);
-    device.inner().send(command).await;
+    let command = device.inner().encode(&Message::HwStopUpdateMsgs, 0)?;
+    device.inner().send(command).await?;
+    device.inner().set_update_messages(false).await;
     log::info!("{device} STOP_UPDATE_MESSAGES (success)");
+    Ok(())
 }
@@ -0,0 +1,110 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use crate::apt::id::MOT_GET_VELPARAMS;
+use crate::apt::{Message, VelParams};
+use crate::devices::escalate;
+use crate::error::cmd::Applied;
+use crate::error::Error;
+use crate::functions::set_and_verify;
+use crate::traits::{ThorlabsDevice, UnitConversion, Units};
+
+#[doc = include_str!("../documentation/get_velocity_params.md")]
+pub(crate) async fn get_velocity_params<A, const CH: usize>(
+    device: &A,
+    channel: usize,
+) -> Result<(f64, f64), Error>
+where
+    A: ThorlabsDevice<CH> + UnitConversion,
+{
+    log::info!("{device} CHANNEL {channel} GET_VELPARAMS (requested)");
+    // Subscribe to GET_VELPARAMS broadcast channel
+    let rx = device.inner().receiver(&MOT_GET_VELPARAMS, channel).await?;
+    if rx.is_new() {
+        // No GET_VELPARAMS response pending from the device. Send REQ_VELPARAMS command.
+        log::info!("{device} CHANNEL {channel} GET_VELPARAMS (is new)");
+        let command = device.inner().encode(
+            &Message::MotReqVelParams {
+                channel: channel as u8,
+            },
+            channel,
+        )?;
+        device.inner().send(command).await?;
+    }
+    // Wait for GET_VELPARAMS response
+    let response = device
+        .inner()
+        .receive(rx, &MOT_GET_VELPARAMS, channel)
+        .await?;
+    log::info!("{device} CHANNEL {channel} GET_VELPARAMS (responded)");
+    // Parse the GET_VELPARAMS response
+    let params = VelParams::decode(&response).map_err(escalate)?;
+    log::info!("{device} CHANNEL {channel} GET_VELPARAMS (success)");
+    Ok(decode(device, &params))
+}
+
+#[doc = include_str!("../documentation/set_velocity_params.md")]
+pub(crate) async fn set_velocity_params<A, const CH: usize>(
+    device: &A,
+    channel: usize,
+    max_velocity: f64,
+    acceleration: f64,
+) -> Result<(), Error>
+where
+    A: ThorlabsDevice<CH> + UnitConversion,
+{
+    log::info!(
+        "{device} CHANNEL {channel} SET_VELPARAMS (requested) MAX_VELOCITY {max_velocity} \
+         ACCELERATION {acceleration}"
+    );
+    let params = VelParams {
+        channel: channel as u16,
+        min_velocity: 0, // Currently unused by Thorlabs devices
+        acceleration: i32::from_le_bytes(*A::acceleration_from_f64(acceleration)),
+        max_velocity: i32::from_le_bytes(*A::velocity_from_f64(max_velocity)),
+    };
+    // The device may clamp or round the requested values. The minimum velocity is not compared.
+    let applied = |actual: VelParams| {
+        let (max_velocity, acceleration) = decode(device, &actual);
+        let actual = VelParams {
+            min_velocity: params.min_velocity,
+            ..actual
+        };
+        (actual != params).then_some(Applied::Velocity {
+            max_velocity,
+            acceleration,
+        })
+    };
+    set_and_verify(
+        device,
+        channel,
+        Message::MotSetVelParams(params),
+        Message::MotReqVelParams {
+            channel: channel as u8,
+        },
+        &MOT_GET_VELPARAMS,
+        VelParams::decode,
+        applied,
+    )
+    .await?;
+    log::info!("{device} CHANNEL {channel} SET_VELPARAMS (success)");
+    Ok(())
+}
+
+/// Converts the velocity parameters from device units to real-world units. Returns the maximum
+/// velocity (mm/s) and acceleration (mm/s²).
+fn decode<A>(device: &A, params: &VelParams) -> (f64, f64)
+where
+    A: UnitConversion,
+{
+    let max_velocity = Units::velocity_from_slice(&params.max_velocity.to_le_bytes());
+    let acceleration = Units::acceleration_from_slice(&params.acceleration.to_le_bytes());
+    (device.decode(max_velocity), device.decode(acceleration))
+}
@@ -10,8 +10,10 @@ modification, are permitted provided that the conditions of the LICENSE are met.
 
 /* ------------------------------------------------------------------------------ Public modules */
 
+pub mod apt;
 pub mod devices;
 pub mod error;
+pub mod transports;
 
 /* ----------------------------------------------------------------------------- Private modules */
 
@@ -37,7 +39,9 @@ mod py {
 /* ------------------------------------------------------------------------------ Public Exports */
 
 pub use devices::*;
-pub use traits::ThorlabsDevice;
+pub use error::Error;
+pub use messages::{Event, HomeSettings, JogSettings, LimitSwitchSettings, Metadata, StatusUpdate};
+pub use traits::{ThorlabsDevice, Transport};
 
 /* --------------------------------------------------------------------------------------- Tests */
 
@@ -54,10 +58,55 @@ mod tests {
             .try_init();
     }
 
+    /// Waits until the condition is `True`. Panics if the condition is not met within five seconds.
+    fn wait(condition: impl Fn() -> bool) {
+        use std::time::{Duration, Instant};
+
+        let start = Instant::now();
+        while !condition() {
+            assert!(start.elapsed() < Duration::from_secs(5), "Timed out");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// A session file with a unique name in the temporary directory, so that concurrent test runs
+    /// do not collide. The file is deleted when the [`Session`] is dropped.
+    struct Session(std::path::PathBuf);
+
+    impl Session {
+        /// Returns a new [`Session`] path. The file is not created.
+        fn new() -> Self {
+            use std::sync::atomic::{AtomicUsize, Ordering};
+
+            static COUNT: AtomicUsize = AtomicUsize::new(0);
+            let count = COUNT.fetch_add(1, Ordering::SeqCst);
+            let name = format!("thormotion-{}-{count}.txt", std::process::id());
+            Self(std::env::temp_dir().join(name))
+        }
+
+        /// Writes the contents to a new [`Session`] file.
+        fn write(contents: &str) -> Self {
+            let session = Self::new();
+            std::fs::write(&session.0, contents).unwrap();
+            session
+        }
+
+        /// Returns a [`Replay`][crate::transports::Replay] transport for the session file.
+        fn replay(&self) -> crate::transports::Replay {
+            crate::transports::Replay::from_file(&self.0).unwrap()
+        }
+    }
+
+    impl Drop for Session {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
     #[test]
     fn show_devices() {
         logger(log::LevelFilter::Trace);
-        crate::show_devices()
+        crate::show_devices().unwrap();
     }
 
     #[test]
@@ -65,6 +114,1024 @@ mod tests {
         logger(log::LevelFilter::Trace);
         let mut device = KDC101::new("27XXX").unwrap();
         device.open().unwrap();
-        device.identify();
+        device.identify().unwrap();
+    }
+
+    #[test]
+    fn simulated_kdc101_status() {
+        logger(log::LevelFilter::Trace);
+        let mut device = KDC101::simulated("27000001").unwrap();
+        device.open().unwrap();
+        device.identify().unwrap();
+        assert!(device.is_channel_enabled().unwrap());
+        device.set_channel_enabled(false).unwrap();
+        assert!(!device.is_channel_enabled().unwrap());
+        device.set_channel_enabled(true).unwrap();
+        assert!(!device.is_homed().unwrap());
+        assert!(!device.in_motion().unwrap());
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn simulated_kdc101_move() {
+        logger(log::LevelFilter::Trace);
+        let mut device = KDC101::simulated("27000002").unwrap();
+        device.open().unwrap();
+        device.home().unwrap();
+        assert!(device.is_homed().unwrap());
+        device.move_absolute(0.5).unwrap();
+        assert!((device.get_position().unwrap() - 0.5).abs() < 1E-4);
+        device.move_relative(0.25).unwrap();
+        assert!((device.get_position().unwrap() - 0.75).abs() < 1E-4);
+        device.move_absolute(0.0).unwrap(); // Reverse limit switch
+        assert_eq!(device.get_status_bits().unwrap() & 0x00000002, 0x00000002);
+        device.stop().unwrap();
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn simulated_kdc101_velocity_params() {
+        use crate::Error;
+        use crate::error::cmd::{self, Applied};
+
+        logger(log::LevelFilter::Trace);
+        let mut device = KDC101::simulated("27000023").unwrap();
+        device.open().unwrap();
+        device.set_velocity_params(1.2, 0.8).unwrap();
+        let (max_velocity, acceleration) = device.get_velocity_params().unwrap();
+        assert!((max_velocity - 1.2).abs() < 1E-4);
+        assert!((acceleration - 0.8).abs() < 1E-2);
+        // The simulated stage clamps the maximum velocity to 2.6 mm/s
+        let result = device.set_velocity_params(5.0, 0.8);
+        let Err(Error::Command(cmd::Error::NotApplied(Applied::Velocity { max_velocity, .. }))) =
+            result
+        else {
+            panic!("Expected NotApplied, got {result:?}");
+        };
+        assert!((max_velocity - 2.6).abs() < 1E-4);
+        let (max_velocity, _) = device.get_velocity_params().unwrap();
+        assert!((max_velocity - 2.6).abs() < 1E-4);
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn simulated_kdc101_jog() {
+        use crate::JogSettings;
+        use crate::apt::{Direction, JogMode, StopMode};
+
+        logger(log::LevelFilter::Trace);
+        let mut device = KDC101::simulated("27000024").unwrap();
+        device.open().unwrap();
+        let mut jog = JogSettings {
+            mode: JogMode::Step,
+            step_size: 0.2,
+            velocity: 2.0,
+            acceleration: 1.0,
+            stop_mode: StopMode::Profiled,
+        };
+        device.set_jog_params(jog).unwrap();
+        let actual = device.get_jog_params().unwrap();
+        assert_eq!(actual.mode, JogMode::Step);
+        assert_eq!(actual.stop_mode, StopMode::Profiled);
+        assert!((actual.step_size - 0.2).abs() < 1E-4);
+        assert!((actual.velocity - 2.0).abs() < 1E-4);
+        let start = device.get_position().unwrap();
+        device.jog(Direction::Forward).unwrap();
+        assert!((device.get_position().unwrap() - start - 0.2).abs() < 1E-4);
+        // A continuous jog which is stopped from another thread returns
+        jog.mode = JogMode::Continuous;
+        device.set_jog_params(jog).unwrap();
+        let handle = std::thread::spawn({
+            let device = device.clone();
+            move || device.jog(Direction::Forward)
+        });
+        wait(|| device.in_motion().unwrap());
+        device.stop().unwrap();
+        handle.join().unwrap().unwrap();
+        let position = device.get_position().unwrap();
+        assert!(position < 25.0);
+        // The next jog waits for its own MOVE_COMPLETED
+        jog.mode = JogMode::Step;
+        device.set_jog_params(jog).unwrap();
+        device.jog(Direction::Reverse).unwrap();
+        assert!((device.get_position().unwrap() - position + 0.2).abs() < 1E-4);
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn simulated_kdc101_home_params() {
+        use crate::apt::{Direction, LimitSwitch};
+        use crate::error::cmd::{self, Applied};
+        use crate::{Error, HomeSettings};
+
+        logger(log::LevelFilter::Trace);
+        let mut device = KDC101::simulated("27000025").unwrap();
+        device.open().unwrap();
+        let mut home = HomeSettings {
+            direction: Direction::Reverse,
+            limit_switch: LimitSwitch::Reverse,
+            velocity: 1.5,
+            offset: 0.5,
+        };
+        device.set_home_params(home).unwrap();
+        let actual = device.get_home_params().unwrap();
+        assert_eq!(actual.direction, Direction::Reverse);
+        assert_eq!(actual.limit_switch, LimitSwitch::Reverse);
+        assert!((actual.velocity - 1.5).abs() < 1E-4);
+        assert!((actual.offset - 0.5).abs() < 1E-4);
+        device.home().unwrap();
+        assert!(device.is_homed().unwrap());
+        assert!((device.get_position().unwrap() - 0.5).abs() < 1E-4);
+        // The simulated stage has 25 mm of travel
+        home.direction = Direction::Forward;
+        home.limit_switch = LimitSwitch::Forward;
+        home.offset = 24.0;
+        device.set_home_params(home).unwrap();
+        device.home().unwrap();
+        assert!((device.get_position().unwrap() - 1.0).abs() < 1E-4);
+        // The simulated stage clamps the homing velocity to 2.6 mm/s
+        home.velocity = 5.0;
+        let result = device.set_home_params(home);
+        let Err(Error::Command(cmd::Error::NotApplied(Applied::Home(applied)))) = result else {
+            panic!("Expected NotApplied, got {result:?}");
+        };
+        assert!((applied.velocity - 2.6).abs() < 1E-4);
+        assert!((applied.offset - 24.0).abs() < 1E-4);
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn simulated_kdc101_limit_switch_params() {
+        use crate::LimitSwitchSettings;
+        use crate::apt::{HardLimit, SoftLimitMode};
+
+        logger(log::LevelFilter::Trace);
+        let mut device = KDC101::simulated("27000020").unwrap();
+        device.open().unwrap();
+        let limits = LimitSwitchSettings {
+            cw_hard_limit: HardLimit::Breaks,
+            ccw_hard_limit: HardLimit::Breaks,
+            swapped: true,
+            cw_soft_limit: 1.0,
+            ccw_soft_limit: 0.0,
+            soft_limit_mode: SoftLimitMode::Profiled,
+            rotation: true,
+        };
+        device.set_limit_switch_params(limits).unwrap();
+        let actual = device.get_limit_switch_params().unwrap();
+        assert_eq!(actual.cw_hard_limit, HardLimit::Breaks);
+        assert_eq!(actual.soft_limit_mode, SoftLimitMode::Profiled);
+        assert!(actual.swapped && actual.rotation);
+        assert!((actual.cw_soft_limit - 1.0).abs() < 1E-4);
+        // The simulated stage stops at the forward software limit
+        device.move_absolute(0.9).unwrap();
+        device.move_relative(0.2).unwrap();
+        assert!((device.get_position().unwrap() - 1.0).abs() < 1E-4);
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn recreate_device() {
+        logger(log::LevelFilter::Trace);
+        // The registry holds the last reference to the first device when the second is created
+        let device = KDC101::simulated("27000099").unwrap();
+        drop(device);
+        let mut device = KDC101::simulated("27000099").unwrap();
+        device.open().unwrap();
+        assert!(device.is_channel_enabled().unwrap());
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn closed_device_error() {
+        use crate::Error;
+        use crate::error::cmd;
+
+        logger(log::LevelFilter::Trace);
+        let device = KDC101::simulated("27000008").unwrap();
+        let result = device.is_channel_enabled();
+        assert!(matches!(
+            result,
+            Err(Error::Command(cmd::Error::DeviceClosed))
+        ));
+    }
+
+    #[test]
+    fn response_timeout() {
+        use std::time::Duration;
+
+        use crate::Error;
+        use crate::error::cmd;
+
+        logger(log::LevelFilter::Trace);
+        // The first REQ_STATUS_BITS is never answered. The second is answered immediately.
+        let session = Session::write(concat!(
+            "0.0 TX 29 04 01 00 50 01\n",
+            "1.0 TX 29 04 01 00 50 01\n",
+            "1.0 RX 2A 04 06 00 81 50 01 00 00 04 00 80\n",
+        ));
+        let mut device = KDC101::with_transport("27000009", session.replay()).unwrap();
+        device.open().unwrap();
+        device.set_timeout(Some(Duration::from_millis(100)));
+        let result = device.get_status_bits();
+        assert!(matches!(
+            result,
+            Err(Error::Command(cmd::Error::Timeout(_)))
+        ));
+        // The pending sender was released, so a new REQ_STATUS_BITS is sent
+        assert_eq!(device.get_status_bits().unwrap(), 0x80000400);
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn unhandled_messages() {
+        logger(log::LevelFilter::Trace);
+        // Unknown header-only and header-plus-payload messages arrive before GET_STATUS_BITS
+        let session = Session::write(concat!(
+            "0.0 TX 29 04 01 00 50 01\n",
+            "0.0 RX 98 09 00 00 01 50 ",
+            "99 09 04 00 81 50 AA BB CC DD ",
+            "2A 04 06 00 81 50 01 00 00 04 00 80\n",
+        ));
+        let mut device = KDC101::with_transport("27000010", session.replay()).unwrap();
+        device.open().unwrap();
+        assert_eq!(device.get_status_bits().unwrap(), 0x80000400);
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn channel_routing() {
+        use crate::Error;
+        use crate::apt::Addressing;
+        use crate::error::cmd;
+        use crate::messages::{Dispatcher, Metadata};
+
+        logger(log::LevelFilter::Trace);
+        const GET_STATUS_BITS: [u8; 2] = [0x2A, 0x04];
+        const GET_CHANENABLESTATE: [u8; 2] = [0x12, 0x02];
+        let ids = [
+            Metadata::payload(GET_STATUS_BITS, 12),
+            Metadata::header(GET_CHANENABLESTATE),
+        ];
+        let dispatcher = Dispatcher::<2>::new(&ids, "27000011", Addressing::Generic);
+        smol::block_on(async {
+            let one = dispatcher.receiver(&GET_STATUS_BITS, 1).await.unwrap();
+            let two = dispatcher.receiver(&GET_STATUS_BITS, 2).await.unwrap();
+            let bay = dispatcher.receiver(&GET_CHANENABLESTATE, 2).await.unwrap();
+            // Responses arrive out of order. Channel ident is the first two bytes of the payload.
+            let status = |ch: u8| [0x2A, 0x04, 0x06, 0x00, 0x81, 0x50, ch, 0x00, ch, 0, 0, 0];
+            dispatcher.dispatch(status(2).into()).await;
+            dispatcher.dispatch(status(1).into()).await;
+            // Bay-addressed units identify the channel using the source byte
+            let enabled = [0x12, 0x02, 0x01, 0x01, 0x01, 0x22];
+            dispatcher.dispatch(enabled.into()).await;
+            assert_eq!(one.receive().await.unwrap()[8], 1);
+            assert_eq!(two.receive().await.unwrap()[8], 2);
+            assert_eq!(bay.receive().await.unwrap()[5], 0x22);
+            // Channels are numbered from one
+            for channel in [0, 3] {
+                assert!(matches!(
+                    dispatcher.receiver(&GET_STATUS_BITS, channel).await,
+                    Err(Error::Command(cmd::Error::InvalidChannel(_)))
+                ));
+            }
+        });
+    }
+
+    #[test]
+    fn device_fault() {
+        use smol::stream::StreamExt;
+
+        use crate::Error;
+        use crate::error::cmd;
+
+        logger(log::LevelFilter::Trace);
+        // REQ_STATUS_BITS is answered with HW_RICHRESPONSE instead of GET_STATUS_BITS
+        let mut fault = vec![0x81, 0x00, 0x44, 0x00, 0x81, 0x50, 0x29, 0x04, 0x05, 0x00];
+        fault.extend(b"Motor overheating");
+        fault.resize(74, 0x00);
+        let fault = fault.iter().map(|b| format!("{b:02X}"));
+        let session = format!(
+            "0.0 TX 29 04 01 00 50 01\n0.0 RX {}\n",
+            fault.collect::<Vec<_>>().join(" ")
+        );
+        let session = Session::write(&session);
+        let mut device = KDC101::with_transport("27000012", session.replay()).unwrap();
+        let mut faults = device.faults();
+        device.open().unwrap();
+        let Err(Error::Command(cmd::Error::Fault(fault))) = device.get_status_bits() else {
+            panic!("Expected a device fault");
+        };
+        assert_eq!(fault.code, 0x0005);
+        assert_eq!(fault.channel, None);
+        assert_eq!(fault.message_id, Some([0x29, 0x04]));
+        assert_eq!(fault.description, "Motor overheating");
+        assert_eq!(smol::block_on(faults.next()), Some(fault));
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn events() {
+        use crate::apt::{Message, StatusBits};
+
+        logger(log::LevelFilter::Trace);
+        // An unknown message arrives before GET_STATUS_BITS
+        let session = Session::write(concat!(
+            "0.0 TX 29 04 01 00 50 01\n",
+            "0.0 RX 98 09 00 00 01 50 ",
+            "2A 04 06 00 81 50 01 00 00 04 00 80\n",
+        ));
+        let mut device = KDC101::with_transport("27000015", session.replay()).unwrap();
+        let mut events = device.events();
+        device.open().unwrap();
+        assert_eq!(device.get_status_bits().unwrap(), 0x80000400);
+        let unknown = events.next().unwrap();
+        assert_eq!(unknown.id(), [0x98, 0x09]);
+        assert_eq!(unknown.message, None);
+        let status = events.next().unwrap();
+        assert_eq!(status.channel, 1);
+        assert_eq!(
+            status.message,
+            Some(Message::MotGetStatusBits(StatusBits {
+                channel: 1,
+                status_bits: 0x80000400
+            }))
+        );
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn status_stream() {
+        use crate::traits::UnitConversion;
+
+        logger(log::LevelFilter::Trace);
+        // START_UPDATE_MESSAGES is followed by 70 updates, which is more than the stream buffers.
+        // An unknown message marks the end of the updates.
+        let update = |i: u8| {
+            let update = [
+                0x91, 0x04, 0x0E, 0x00, 0x81, 0x50, 0x01, 0x00, i, 0x00, 0x00, 0x00,
+            ];
+            let update = update.iter().chain(&[0x00; 8]);
+            update
+                .map(|b| format!("{b:02X}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let updates = (0..70).map(update).collect::<Vec<_>>().join(" ");
+        let session = format!("0.0 TX 11 00 00 00 50 01\n0.0 RX {updates} 98 09 00 00 01 50\n");
+        let session = Session::write(&session);
+        let mut device = KDC101::with_transport("27000016", session.replay()).unwrap();
+        let mut stream = device.status_stream();
+        let mut events = device.events();
+        device.open().unwrap();
+        device.start_update_messages().unwrap();
+        assert!(events.any(|event| event.id() == [0x98, 0x09]));
+        // The oldest updates are discarded and reported as missed
+        let first = stream.next().unwrap();
+        assert_eq!(first.missed, 6);
+        assert_eq!(first.channel, 1);
+        assert_eq!(first.position, 6.0 / KDC101::DISTANCE_ANGLE_SCALE_FACTOR);
+        let second = stream.next().unwrap();
+        assert_eq!(second.missed, 0);
+        assert!(second.timestamp >= first.timestamp);
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn cached_status() {
+        use std::time::Duration;
+
+        use crate::traits::UnitConversion;
+
+        logger(log::LevelFilter::Trace);
+        // START_UPDATE_MESSAGES is followed by a single homed status update
+        let session = Session::write(
+            "0.0 TX 11 00 00 00 50 01\n0.0 RX 91 04 0E 00 81 50 01 00 10 00 00 00 00 00 00 00 00 \
+             04 00 00\n",
+        );
+        let mut device = KDC101::with_transport("27000018", session.replay()).unwrap();
+        let mut stream = device.status_stream();
+        device.open().unwrap();
+        device.start_update_messages().unwrap();
+        // The status is cached before it is broadcast to subscribers
+        stream.next().unwrap();
+        // The cached status is returned without sending a request
+        let max_age = Duration::from_secs(1);
+        let position = device.get_position_with_max_age(max_age).unwrap();
+        assert_eq!(position, 16.0 / KDC101::DISTANCE_ANGLE_SCALE_FACTOR);
+        assert!(device.is_homed_with_max_age(max_age).unwrap());
+        assert!(!device.in_motion_with_max_age(max_age).unwrap());
+        // A stale cache falls back to a request, which the replay session does not contain
+        assert!(device.get_status_with_max_age(Duration::ZERO).is_err());
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn hw_disconnect() {
+        use crate::Error;
+        use crate::apt::Message;
+        use crate::error::cmd;
+
+        logger(log::LevelFilter::Trace);
+        // A message with an invalid source byte arrives before HW_DISCONNECT
+        let session = Session::write(concat!(
+            "0.0 TX 11 00 00 00 50 01\n",
+            "0.0 RX 44 04 01 00 01 42 ",
+            "02 00 00 00 01 50\n",
+        ));
+        let mut device = KDC101::with_transport("27000019", session.replay()).unwrap();
+        let mut events = device.events();
+        device.open().unwrap();
+        device.start_update_messages().unwrap();
+        let disconnect = events.next().unwrap();
+        assert_eq!(disconnect.message, Some(Message::HwDisconnect));
+        wait(|| !device.is_open());
+        // The device is closed, rather than disconnected and waiting to reconnect
+        assert!(matches!(
+            device.get_status_bits(),
+            Err(Error::Command(cmd::Error::DeviceClosed))
+        ));
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn keep_alive() {
+        use std::io;
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        use crate::Transport;
+        use crate::traits::{BoxFuture, Incoming};
+
+        /// A transport which captures every message sent by the host and never responds.
+        #[derive(Debug, Clone, Default)]
+        struct Capture(Arc<Mutex<Vec<Vec<u8>>>>);
+
+        impl Transport for Capture {
+            fn open(&mut self) -> BoxFuture<'_, io::Result<Incoming>> {
+                Box::pin(async { Ok(Box::pin(smol::stream::pending()) as Incoming) })
+            }
+
+            fn close(&mut self) -> BoxFuture<'_, io::Result<()>> {
+                Box::pin(async { Ok(()) })
+            }
+
+            fn send(&mut self, bytes: Vec<u8>) -> BoxFuture<'_, io::Result<()>> {
+                self.0.lock().unwrap().push(bytes);
+                Box::pin(async { Ok(()) })
+            }
+        }
+
+        logger(log::LevelFilter::Trace);
+        const ACK: [u8; 6] = [0x92, 0x04, 0x00, 0x00, 0x50, 0x01];
+        let capture = Capture::default();
+        let sent = capture.0.clone(); // Inexpensive Arc Clone
+        let session = Session::new();
+        let mut device = KDC101::with_transport("27000017", capture).unwrap();
+        device.record(session.0.clone()).unwrap();
+        device.open().unwrap();
+        let acks = || sent.lock().unwrap().iter().filter(|bytes| **bytes == ACK).count();
+        device.start_update_messages().unwrap();
+        wait(|| acks() >= 2);
+        // Keep-alive messages stop with the update messages. One may already be in flight.
+        device.stop_update_messages().unwrap();
+        let stopped = acks();
+        std::thread::sleep(Duration::from_millis(500)); // Five keep-alive intervals in tests
+        assert!(acks() <= stopped + 1);
+        device.close().unwrap();
+        // Keep-alive messages are recorded, but are not replayed
+        let recorded = std::fs::read_to_string(&session.0).unwrap();
+        assert!(recorded.contains(" KA 92 04 00 00 50 01\n"));
+        let mut device = KDC101::with_transport("27000021", session.replay()).unwrap();
+        device.open().unwrap();
+        device.start_update_messages().unwrap();
+        device.stop_update_messages().unwrap();
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn raw_commands() {
+        use crate::error::cmd;
+        use crate::{Error, Metadata, ThorlabsDevice};
+
+        logger(log::LevelFilter::Trace);
+        // HW_GET_INFO is not registered by default
+        const HW_REQ_INFO: [u8; 2] = [0x05, 0x00];
+        const HW_GET_INFO: [u8; 2] = [0x06, 0x00];
+        let mut info = vec![0x06, 0x00, 0x54, 0x00, 0x81, 0x50];
+        info.resize(90, 0xAB);
+        let info = info.iter().map(|b| format!("{b:02X}"));
+        let session = format!(
+            "0.0 TX 23 02 01 00 50 01\n0.0 TX 05 00 01 00 50 01\n0.0 RX {}\n",
+            info.collect::<Vec<_>>().join(" ")
+        );
+        let session = Session::write(&session);
+        let unregistered = KDC101::with_transport("27000014", session.replay()).unwrap();
+        assert!(matches!(
+            unregistered.request_raw(HW_REQ_INFO, HW_GET_INFO, 1, &[]),
+            Err(Error::Command(cmd::Error::UnknownCommand(HW_GET_INFO)))
+        ));
+        let responses = [Metadata::payload(HW_GET_INFO, 90)];
+        let mut device = KDC101::with_responses("27000013", session.replay(), &responses).unwrap();
+        device.open().unwrap();
+        device
+            .send_raw(vec![0x23, 0x02, 0x01, 0x00, 0x50, 0x01])
+            .unwrap();
+        let response = device
+            .request_raw(HW_REQ_INFO, HW_GET_INFO, 1, &[])
+            .unwrap();
+        assert_eq!(response.len(), 90);
+        assert_eq!(response[6..], [0xAB; 84]);
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn record_and_replay() {
+        logger(log::LevelFilter::Trace);
+        let session = Session::new();
+        let mut device = KDC101::simulated("27000003").unwrap();
+        device.record(session.0.clone()).unwrap();
+        device.open().unwrap();
+        let recorded = (
+            device.is_channel_enabled().unwrap(),
+            device.get_status_bits().unwrap(),
+        );
+        device.home().unwrap();
+        device.stop_recording();
+        device.close().unwrap();
+
+        let mut device = KDC101::with_transport("27000004", session.replay()).unwrap();
+        device.open().unwrap();
+        let replayed = (
+            device.is_channel_enabled().unwrap(),
+            device.get_status_bits().unwrap(),
+        );
+        device.home().unwrap();
+        device.close().unwrap();
+        assert_eq!(recorded, replayed);
+    }
+
+    #[test]
+    fn tcp_bridge() {
+        use smol::io::{AsyncReadExt, AsyncWriteExt};
+        use smol::net::TcpListener;
+        use smol::stream::StreamExt;
+
+        use crate::traits::Transport;
+        use crate::transports::{Simulator, Tcp};
+
+        logger(log::LevelFilter::Trace);
+        // Serve a simulated device over a local TCP socket
+        let listener = smol::block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+        let address = listener.local_addr().unwrap();
+        let _server = smol::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut simulator = Simulator::new::<KDC101>();
+            let mut incoming = simulator.open().await.unwrap();
+            let mut writer = socket.clone();
+            let _forward = smol::spawn(async move {
+                while let Some(Ok(bytes)) = incoming.next().await {
+                    writer.write_all(&bytes).await.unwrap();
+                }
+            });
+            let mut buf = [0u8; 256];
+            while let Ok(n @ 1..) = socket.read(&mut buf).await {
+                simulator.send(buf[..n].to_vec()).await.unwrap();
+            }
+        });
+        let mut device = KDC101::with_transport("27000006", Tcp::new(address.to_string())).unwrap();
+        device.open().unwrap();
+        assert!(device.is_channel_enabled().unwrap());
+        device.home().unwrap();
+        device.move_absolute(0.5).unwrap();
+        assert!((device.get_position().unwrap() - 0.5).abs() < 1E-4);
+        device.close().unwrap();
+    }
+
+    #[cfg(unix)]
+    /// Opens a pseudo-terminal. Returns the master end, which plays the role of the Thorlabs
+    /// device, and the path to the slave end.
+    fn pty() -> (std::fs::File, String) {
+        use std::ffi::CStr;
+        use std::fs::File;
+        use std::os::fd::FromRawFd;
+
+        let mut name = [0 as libc::c_char; 64];
+        let master = unsafe {
+            let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+            assert!(fd >= 0);
+            assert_eq!(libc::grantpt(fd), 0);
+            assert_eq!(libc::unlockpt(fd), 0);
+            assert_eq!(libc::ptsname_r(fd, name.as_mut_ptr(), name.len()), 0);
+            File::from_raw_fd(fd)
+        };
+        let path = unsafe { CStr::from_ptr(name.as_ptr()) }.to_str().unwrap();
+        (master, path.to_string())
+    }
+
+    /// Answers one REQ_CHANENABLESTATE on the master end of a pseudo-terminal, then returns the
+    /// master end so that it is not closed while the device is open.
+    fn respond_enabled(mut master: std::fs::File) -> std::thread::JoinHandle<std::fs::File> {
+        use std::io::{Read, Write};
+
+        std::thread::spawn(move || {
+            let mut req = [0u8; 6];
+            master.read_exact(&mut req).unwrap();
+            assert_eq!(req, [0x11, 0x02, 0x01, 0x00, 0x50, 0x01]); // REQ_CHANENABLESTATE
+            master
+                .write_all(&[0x12, 0x02, 0x01, 0x01, 0x01, 0x50])
+                .unwrap();
+            master
+        })
+    }
+
+    #[test]
+    fn serial_port() {
+        logger(log::LevelFilter::Trace);
+        let (master, path) = pty();
+        let serial = crate::transports::Serial::new(path);
+        let mut device = KDC101::with_transport("27000005", serial).unwrap();
+        device.open().unwrap();
+        let responder = respond_enabled(master);
+        assert!(device.is_channel_enabled().unwrap());
+        let _master = responder.join().unwrap();
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn serial_path() {
+        logger(log::LevelFilter::Trace);
+        let (master, path) = pty();
+        // A pseudo-terminal does not belong to a USB device, so the serial number is unknown
+        assert!(KDC101::new(path.as_str()).is_err());
+        // Entries in /dev/serial/by-id are named after the serial number of the device
+        let dir = Session::new();
+        std::fs::create_dir(&dir.0).unwrap();
+        let link = dir.0.join("usb-Thorlabs_Brushed_Motor_Controller_27000026-if00-port0");
+        std::os::unix::fs::symlink(&path, &link).unwrap();
+        let mut device = KDC101::new(link.to_str().unwrap()).unwrap();
+        assert_eq!(device.to_string(), "KDC101 27000026");
+        device.open().unwrap();
+        let responder = respond_enabled(master);
+        assert!(device.is_channel_enabled().unwrap());
+        let _master = responder.join().unwrap();
+        device.close().unwrap();
+        std::fs::remove_dir_all(&dir.0).unwrap();
+    }
+
+    #[test]
+    fn hot_plug() {
+        use crate::transports::Simulator;
+
+        logger(log::LevelFilter::Trace);
+        let simulator = Simulator::new::<KDC101>();
+        let plug = simulator.plug();
+        let mut device = KDC101::with_transport("27000007", simulator).unwrap();
+        device.open().unwrap();
+        assert!(device.is_channel_enabled().unwrap());
+        plug.unplug();
+        wait(|| !device.is_open());
+        plug.replug();
+        wait(|| device.is_open());
+        assert!(device.is_channel_enabled().unwrap());
+        device.close().unwrap();
+    }
+
+    #[test]
+    fn ftdi_deframe() {
+        use crate::transports::FtdiDiagnostics;
+
+        logger(log::LevelFilter::Trace);
+        // A 100 byte payload split across two 64 byte packets, each prefixed with status bytes
+        let payload: Vec<u8> = (0..100).collect();
+        let mut transfer = vec![0x31, 0x60];
+        transfer.extend_from_slice(&payload[..62]);
+        transfer.extend_from_slice(&[0x31, 0x62]); // Overrun error
+        transfer.extend_from_slice(&payload[62..]);
+        let diagnostics = FtdiDiagnostics::default();
+        assert_eq!(diagnostics.deframe(&transfer, 64), payload);
+        assert_eq!(diagnostics.deframe(&[0x31, 0x60], 64), []); // Status bytes only
+        let status = diagnostics.status();
+        assert!(status.cts() && status.dsr() && !status.is_error());
+        assert_eq!(diagnostics.overrun_errors(), 1);
+        assert_eq!(diagnostics.framing_errors(), 0);
+    }
+
+    #[test]
+    fn apt_round_trip() {
+        use crate::apt::{
+            self,
+            ChanEnableState,
+            Direction,
+            HardLimit,
+            HomeParams,
+            JogMode,
+            JogParams,
+            LimSwitchParams,
+            LimitSwitch,
+            Message,
+            Move,
+            RichResponse,
+            SoftLimitMode,
+            StopMode,
+            UStatus,
+            VelParams,
+        };
+        use crate::error::apt::Error;
+
+        let status = UStatus {
+            channel: 1,
+            position: -12345,
+            velocity: 678,
+            current: -9,
+            status_bits: 0x80000400,
+        };
+        let messages = [
+            Message::HwDisconnect,
+            Message::HwStartUpdateMsgs,
+            Message::HwResponse { code: 0x1234 },
+            Message::HwRichResponse(RichResponse {
+                message_id: [0x53, 0x04],
+                code: 7,
+                notes: String::from("Stage not homed"),
+            }),
+            Message::ModSetChanEnableState(ChanEnableState {
+                channel: 1,
+                enabled: false,
+            }),
+            Message::MotMoveRelative(Move {
+                channel: 1,
+                distance: -34555,
+            }),
+            Message::MotMoveAbsoluteFromParams { channel: 1 },
+            Message::MotSetVelParams(VelParams {
+                channel: 1,
+                min_velocity: 0,
+                acceleration: 396,
+                max_velocity: 1159472,
+            }),
+            Message::MotReqVelParams { channel: 1 },
+            Message::MotSetJogParams(JogParams {
+                channel: 1,
+                mode: JogMode::Continuous,
+                step_size: 6911,
+                min_velocity: 0,
+                acceleration: 264,
+                max_velocity: 1545963,
+                stop_mode: StopMode::Immediate,
+            }),
+            Message::MotMoveJog {
+                channel: 1,
+                direction: Direction::Reverse,
+            },
+            Message::MotSetHomeParams(HomeParams {
+                channel: 1,
+                direction: Direction::Forward,
+                limit_switch: LimitSwitch::Forward,
+                velocity: 1342177,
+                offset: 68678,
+            }),
+            Message::MotReqHomeParams { channel: 1 },
+            Message::MotSetLimSwitchParams(LimSwitchParams {
+                channel: 1,
+                cw_hard_limit: HardLimit::MakesHomeOnly,
+                ccw_hard_limit: HardLimit::Breaks,
+                swapped: true,
+                cw_soft_limit: 1717987,
+                ccw_soft_limit: -34359,
+                soft_limit_mode: SoftLimitMode::Immediate,
+                rotation: false,
+            }),
+            Message::MotReqLimSwitchParams { channel: 1 },
+            Message::MotMoveStop {
+                channel: 1,
+                mode: StopMode::Immediate,
+            },
+            Message::MotGetUStatusUpdate(status),
+        ];
+        for message in messages {
+            assert_eq!(apt::decode(&apt::encode(&message)), Ok(message));
+        }
+        // Responses from a device use the same encoding with the address bytes swapped
+        let bytes =
+            apt::encode_addressed(&Message::MotMoveCompleted(status), apt::HOST, apt::DEVICE);
+        assert_eq!(bytes[4..6], [apt::HOST | 0x80, apt::DEVICE]);
+        assert_eq!(UStatus::decode(&bytes), Ok(status));
+        // Malformed messages
+        assert_eq!(apt::decode(&[0x29, 0x04]), Err(Error::Truncated(2)));
+        assert_eq!(
+            apt::decode(&[0x2A, 0x04, 0x02, 0x00, 0xD0, 0x01, 0x01, 0x00]),
+            Err(Error::InvalidLength {
+                id: [0x2A, 0x04],
+                expected: 12,
+                actual: 8
+            })
+        );
+        assert_eq!(
+            apt::decode(&[0x98, 0x09, 0x01, 0x00, 0x50, 0x01]),
+            Err(Error::UnknownId([0x98, 0x09]))
+        );
+        assert_eq!(
+            apt::decode(&[0x12, 0x02, 0x01, 0x03, 0x01, 0x50]),
+            Err(Error::InvalidValue {
+                id: [0x12, 0x02],
+                value: 0x03
+            })
+        );
+        assert_eq!(
+            UStatus::decode(&apt::encode(&Message::MotMoveHome { channel: 1 })),
+            Err(Error::UnexpectedMessage([0x43, 0x04]))
+        );
+        // Malformed GET_JOGPARAMS responses
+        let jog = JogParams {
+            channel: 1,
+            mode: JogMode::Step,
+            step_size: 6911,
+            min_velocity: 0,
+            acceleration: 264,
+            max_velocity: 1545963,
+            stop_mode: StopMode::Profiled,
+        };
+        let bytes = apt::encode(&Message::MotGetJogParams(jog));
+        assert_eq!(JogParams::decode(&bytes), Ok(jog));
+        for (index, value) in [(8, 0x03), (26, 0x00)] {
+            // Jog mode and stop mode
+            let mut invalid = bytes.clone();
+            invalid[index] = value;
+            assert_eq!(
+                JogParams::decode(&invalid),
+                Err(Error::InvalidValue {
+                    id: [0x18, 0x04],
+                    value
+                })
+            );
+        }
+        assert_eq!(
+            JogParams::decode(&apt::encode(&Message::MotReqJogParams { channel: 1 })),
+            Err(Error::UnexpectedMessage([0x17, 0x04]))
+        );
+        // Malformed GET_HOMEPARAMS responses
+        let home = HomeParams {
+            channel: 1,
+            direction: Direction::Forward,
+            limit_switch: LimitSwitch::Forward,
+            velocity: 1342177,
+            offset: 68678,
+        };
+        let bytes = apt::encode(&Message::MotGetHomeParams(home));
+        assert_eq!(HomeParams::decode(&bytes), Ok(home));
+        for (index, value) in [(8, 0x00), (10, 0x02)] {
+            // Direction and limit switch
+            let mut invalid = bytes.clone();
+            invalid[index] = value;
+            assert_eq!(
+                HomeParams::decode(&invalid),
+                Err(Error::InvalidValue {
+                    id: [0x42, 0x04],
+                    value
+                })
+            );
+        }
+        assert_eq!(
+            HomeParams::decode(&bytes[..16]),
+            Err(Error::InvalidLength {
+                id: [0x42, 0x04],
+                expected: 20,
+                actual: 16
+            })
+        );
+        assert_eq!(
+            HomeParams::decode(&apt::encode(&Message::MotReqHomeParams { channel: 1 })),
+            Err(Error::UnexpectedMessage([0x41, 0x04]))
+        );
+        // The swapped and rotation stage bits are carried alongside every mode
+        let modes = [
+            SoftLimitMode::Ignore,
+            SoftLimitMode::Immediate,
+            SoftLimitMode::Profiled,
+        ];
+        for (mode, byte) in modes.into_iter().zip([0x81, 0x82, 0x83]) {
+            let params = LimSwitchParams {
+                channel: 1,
+                cw_hard_limit: HardLimit::Makes,
+                ccw_hard_limit: HardLimit::IndexMark,
+                swapped: true,
+                cw_soft_limit: 1717987,
+                ccw_soft_limit: -34359,
+                soft_limit_mode: mode,
+                rotation: true,
+            };
+            let bytes = apt::encode(&Message::MotGetLimSwitchParams(params));
+            assert_eq!(bytes[8], 0x82); // Forward hardware limit with the swapped bit
+            assert_eq!(bytes[20], byte); // Software limit mode with the rotation stage bit
+            assert_eq!(LimSwitchParams::decode(&bytes), Ok(params));
+            let params = LimSwitchParams {
+                swapped: false,
+                rotation: false,
+                ..params
+            };
+            let bytes = apt::encode(&Message::MotGetLimSwitchParams(params));
+            assert_eq!(bytes[20], byte & 0x7F);
+            assert_eq!(LimSwitchParams::decode(&bytes), Ok(params));
+        }
+        // Malformed GET_LIMSWITCHPARAMS responses
+        let params = LimSwitchParams {
+            channel: 1,
+            cw_hard_limit: HardLimit::Makes,
+            ccw_hard_limit: HardLimit::Makes,
+            swapped: false,
+            cw_soft_limit: 1717987,
+            ccw_soft_limit: 0,
+            soft_limit_mode: SoftLimitMode::Ignore,
+            rotation: false,
+        };
+        let bytes = apt::encode(&Message::MotGetLimSwitchParams(params));
+        // The swapped and rotation stage bits are masked before the value is checked
+        let cases = [
+            (8, 0x87, 0x07),  // Forward hardware limit
+            (10, 0x80, 0x80), // Reverse hardware limit
+            (20, 0x84, 0x04), // Software limit mode
+            (20, 0x80, 0x00), // Software limit mode
+        ];
+        for (index, byte, value) in cases {
+            let mut invalid = bytes.clone();
+            invalid[index] = byte;
+            assert_eq!(
+                LimSwitchParams::decode(&invalid),
+                Err(Error::InvalidValue {
+                    id: [0x25, 0x04],
+                    value
+                })
+            );
+        }
+        assert_eq!(
+            LimSwitchParams::decode(&bytes[..18]),
+            Err(Error::InvalidLength {
+                id: [0x25, 0x04],
+                expected: 22,
+                actual: 18
+            })
+        );
+        assert_eq!(
+            LimSwitchParams::decode(&apt::encode(&Message::MotReqLimSwitchParams { channel: 1 })),
+            Err(Error::UnexpectedMessage([0x24, 0x04]))
+        );
+    }
+
+    #[test]
+    fn bay_addressing() {
+        use crate::apt::{self, Addressing, Message, Move};
+
+        // Each bay is addressed by destination byte, with channel ident 1
+        let message = Message::MotMoveRelative(Move {
+            channel: 3,
+            distance: 100,
+        });
+        let bytes = Addressing::Bays.encode(&message, 3).unwrap();
+        assert_eq!(bytes[4..8], [0x23 | 0x80, apt::HOST, 0x01, 0x00]);
+        let bytes = Addressing::Generic.encode(&message, 3).unwrap();
+        assert_eq!(bytes, apt::encode(&message));
+        // Messages which refer to the whole device are sent to the motherboard
+        let bytes = Addressing::Bays.encode(&Message::HwStartUpdateMsgs, 0).unwrap();
+        assert_eq!(bytes[4], apt::MOTHERBOARD);
+        // Channels beyond the last bay cannot be addressed
+        assert_eq!(Addressing::Bays.destination(10), Some(apt::BAY + 10));
+        for channel in [11, 255, 256, usize::MAX] {
+            assert_eq!(Addressing::Bays.destination(channel), None);
+            assert_eq!(Addressing::Bays.encode(&message, channel), None);
+        }
+        // Incoming messages are routed by source byte
+        let response = apt::encode_addressed(
+            &Message::MotMoveHomed { channel: 1 },
+            apt::HOST,
+            apt::BAY + 2,
+        );
+        assert_eq!(Addressing::Bays.channel(&response), Some(2));
+        let response =
+            apt::encode_addressed(&Message::HwStopUpdateMsgs, apt::HOST, apt::MOTHERBOARD);
+        assert_eq!(Addressing::Bays.channel(&response), Some(0));
+        // Messages which are too short to contain a channel ident are not routed
+        assert_eq!(apt::bay(&response[..5]), None);
+        for addressing in [Addressing::Bays, Addressing::Generic] {
+            assert_eq!(addressing.channel(&response[..4]), None);
+        }
+        assert_eq!(Addressing::Generic.channel(&[0x2A, 0x04, 0x01, 0x00, 0x81, 0x50]), None);
+    }
+
+    #[test]
+    fn bay_device() {
+        use crate::apt::Addressing;
+
+        logger(log::LevelFilter::Trace);
+        // REQ_STATUS_BITS is sent to the first bay, which answers from its own source byte
+        let session = Session::write(concat!(
+            "0.0 TX 29 04 01 00 21 01\n",
+            "0.0 RX 2A 04 06 00 81 21 01 00 00 04 00 80\n",
+        ));
+        let mut device =
+            KDC101::with_addressing("27000022", session.replay(), Addressing::Bays).unwrap();
+        device.open().unwrap();
+        assert_eq!(device.get_status_bits().unwrap(), 0x80000400);
+        device.close().unwrap();
     }
 }
@@ -24,8 +24,14 @@ pub(crate) const CMD_LEN_MAX: usize = 255 + 6;
 /// 1. Unique two-byte ID
 /// 2. Total command length
 /// 3. Number of device channels
-#[derive(Debug)]
-pub(crate) struct Metadata<const CHANNELS: usize> {
+///
+/// Pass additional [`Metadata`] to [`KDC101::with_responses`][1] to receive responses which
+/// Thormotion does not register by default. See [`request_raw`][2].
+///
+/// [1]: crate::devices::KDC101::with_responses
+/// [2]: crate::ThorlabsDevice::request_raw
+#[derive(Debug, Clone, Copy)]
+pub struct Metadata<const CHANNELS: usize> {
     /// Unique two-byte identifier for the command
     pub(super) id: [u8; 2],
     /// Total number of bytes in the command
@@ -41,7 +47,12 @@ impl<const CH: usize> Metadata<CH> {
     ///
     /// Currently, no data packet exceeds 255 bytes (Thorlabs APT Protocol, Issue 39, Page 35).
     /// The maximum possible command length is given by [`CMD_LEN_MAX`].
-    pub(crate) const fn payload(id: [u8; 2], length: usize) -> Self {
+    ///
+    /// ### Panics
+    ///
+    /// Panics if the `length` is shorter than the header or longer than [`CMD_LEN_MAX`]. This is a
+    /// compile-time error if the [`Metadata`] is defined as a `const`.
+    pub const fn payload(id: [u8; 2], length: usize) -> Self {
         if length < 6 || length > CMD_LEN_MAX {
             panic!("Invalid command length"); // Compile-time error
         }
@@ -51,7 +62,7 @@ impl<const CH: usize> Metadata<CH> {
     /// Creates a new header-only [`Metadata`] with the specified ID.
     ///
     /// Header-only commands are always six bytes long (Thorlabs APT Protocol, Issue 39, Page 34).
-    pub(crate) const fn header(id: [u8; 2]) -> Self {
+    pub const fn header(id: [u8; 2]) -> Self {
         Self::payload(id, 6)
     }
 }
@@ -59,6 +70,7 @@ impl<const CH: usize> Metadata<CH> {
 #[derive(Debug)]
 pub(crate) struct Command<const CH: usize> {
     /// Unique two-byte identifier for the command
+    #[allow(unused)]
     pub(super) id: [u8; 2],
     /// Total number of bytes in the command
     pub(crate) length: usize,
@@ -82,8 +94,10 @@ impl<const CH: usize> Command<CH> {
         (m.id, cmd)
     }
 
-    pub(super) const fn sender(&self, channel: usize) -> &Mutex<Option<Sender>> {
-        let i = if channel <= 1 { 0 } else { channel - 1 };
-        &self.senders[i]
+    /// Returns the [`Sender`] slot for the specified channel. Channels are numbered from one.
+    ///
+    /// Returns [`None`] if the channel does not exist.
+    pub(super) fn sender(&self, channel: usize) -> Option<&Mutex<Option<Sender>>> {
+        self.senders.get(channel.checked_sub(1)?)
     }
 }
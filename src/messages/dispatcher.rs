@@ -9,33 +9,124 @@ modification, are permitted provided that the conditions of the LICENSE are met.
 */
 
 use std::fmt::{Debug, Display, Formatter};
-use std::sync::Arc;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use ahash::HashMap;
-use async_broadcast::broadcast;
+use async_broadcast::{InactiveReceiver, broadcast};
+use smol::Timer;
 use smol::lock::MutexGuard;
 
-use crate::devices::{abort, bug_abort};
-use crate::messages::{Command, Metadata, Provenance, Receiver, Sender};
+use crate::apt::id::{MOT_GET_USTATUSUPDATE, MOT_MOVE_COMPLETED, MOT_MOVE_STOPPED};
+use crate::apt::{Addressing, UStatus};
+use crate::devices::{bug_abort, escalate};
+use crate::error::{DeviceFault, Error, cmd};
+use crate::messages::session::{Direction, Recorder};
+use crate::messages::{Command, Event, Metadata, Provenance, Receiver, Sender};
+
+/// The number of [`DeviceFault`] reports buffered for each subscriber.
+const FAULTS_CAPACITY: usize = 16;
+
+/// The number of [`Event`] reports buffered for each subscriber.
+const EVENTS_CAPACITY: usize = 64;
+
+/// The number of status updates buffered for each subscriber.
+const UPDATES_CAPACITY: usize = 64;
+
+/// A status update and the time at which the host received it.
+pub(crate) type Update = (Instant, usize, UStatus);
+
+/// The most recent status for each channel and the time at which the host received it.
+type Cache<const CH: usize> = [Option<(Instant, UStatus)>; CH];
 
 /// A thread-safe message dispatcher for handling async `Req → Get` callback patterns.
 ///
 /// This type includes an internal [`Arc`] to enable inexpensive cloning.
 /// The [`Dispatcher`] is released when all clones are dropped.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub(crate) struct Dispatcher<const CH: usize> {
     /// A unique eight-digit serial number that is printed on the Thorlabs device.
     serial_number: String,
+    /// How the host addresses the channels of the device. Incoming messages are routed to the
+    /// [`channel`][1] that they refer to.
+    ///
+    /// [1]: Addressing::channel
+    addressing: Addressing,
     /// A [`HashMap`] of `Message ID` keys and [`Command`] values.
     map: Arc<HashMap<[u8; 2], Command<CH>>>,
+    /// An optional [`Recorder`] that captures every message sent and received by the host.
+    recorder: Arc<Mutex<Option<Recorder>>>,
+    /// A sender for broadcasting [`DeviceFault`] reports to subscribers. See [`faults`][1].
+    ///
+    /// [1]: Dispatcher::faults
+    faults: async_broadcast::Sender<DeviceFault>,
+    /// Keeps the [`faults`][1] channel open while there are no subscribers.
+    ///
+    /// [1]: Dispatcher::faults
+    _faults: InactiveReceiver<DeviceFault>,
+    /// A sender for broadcasting a copy of every incoming message to subscribers. See
+    /// [`events`][1].
+    ///
+    /// [1]: Dispatcher::events
+    events: async_broadcast::Sender<Event>,
+    /// Keeps the [`events`][1] channel open while there are no subscribers.
+    ///
+    /// [1]: Dispatcher::events
+    _events: InactiveReceiver<Event>,
+    /// A sender for broadcasting every `GET_U_STATUS_UPDATE` message to subscribers. See
+    /// [`updates`][1].
+    ///
+    /// [1]: Dispatcher::updates
+    updates: async_broadcast::Sender<Update>,
+    /// Keeps the [`updates`][1] channel open while there are no subscribers.
+    ///
+    /// [1]: Dispatcher::updates
+    _updates: InactiveReceiver<Update>,
+    /// Set to `True` while update messages are enabled. See [`set_update_messages`][1].
+    ///
+    /// [1]: Dispatcher::set_update_messages
+    update_messages: Arc<AtomicBool>,
+    /// The most recent status reported by the device for each channel. See [`cached`][1].
+    ///
+    /// [1]: Dispatcher::cached
+    cache: Arc<Mutex<Cache<CH>>>,
 }
 
 impl<const CH: usize> Dispatcher<CH> {
     /// Constructs a new [`Dispatcher`] from the provided array of command ID bytes.
-    pub(crate) fn new(ids: &[Metadata<CH>], serial_number: &String) -> Self {
+    ///
+    /// If an ID appears more than once, only the first [`Metadata`] is used.
+    pub(crate) fn new(ids: &[Metadata<CH>], serial_number: &str, addressing: Addressing) -> Self {
+        let mut map = HashMap::default();
+        for (id, command) in ids.iter().map(Command::new) {
+            if map.contains_key(&id) {
+                log::warn!("DISPATCHER {serial_number} ignored duplicate command ID {id:02X?}");
+                continue;
+            }
+            map.insert(id, command);
+        }
+        let (mut faults, rx) = broadcast(FAULTS_CAPACITY);
+        faults.set_overflow(true); // Slow subscribers miss the oldest faults
+        let (mut events, events_rx) = broadcast(EVENTS_CAPACITY);
+        events.set_overflow(true); // Slow subscribers miss the oldest events
+        let (mut updates, updates_rx) = broadcast(UPDATES_CAPACITY);
+        updates.set_overflow(true); // Slow subscribers miss the oldest updates
         Self {
-            serial_number: serial_number.clone(),
-            map: Arc::new(HashMap::from_iter(ids.iter().map(Command::new))),
+            serial_number: serial_number.to_string(),
+            addressing,
+            map: Arc::new(map),
+            recorder: Arc::new(Mutex::new(None)),
+            faults,
+            _faults: rx.deactivate(),
+            events,
+            _events: events_rx.deactivate(),
+            updates,
+            _updates: updates_rx.deactivate(),
+            update_messages: Arc::new(AtomicBool::new(false)),
+            cache: Arc::new(Mutex::new([None; CH])),
         }
     }
 
@@ -43,14 +134,38 @@ impl<const CH: usize> Dispatcher<CH> {
         &self.serial_number
     }
 
+    /// Returns how the host addresses the channels of the device.
+    pub(crate) fn addressing(&self) -> Addressing {
+        self.addressing
+    }
+
     /// Returns a reference to the [`Command`] corresponding to the ID.
+    ///
+    /// Returns [`cmd::Error::UnknownCommand`] if the [`Dispatcher`] does not contain the ID.
     #[doc(hidden)]
-    async fn get(&self, id: &[u8]) -> &Command<CH> {
+    async fn get(&self, id: &[u8]) -> Result<&Command<CH>, Error> {
         // SAFETY: Using Dispatcher::get outside this impl block may allow a channel to remain in
         // the Dispatcher::map after sending a message. Use Dispatcher::take instead.
-        self.map
-            .get(id)
-            .unwrap_or_else(|| abort(format!("{self} does not contain command ID {id:02X?}")))
+        self.map.get(id).ok_or_else(|| {
+            log::warn!("{self} does not contain command ID {id:02X?}");
+            escalate(cmd::Error::UnknownCommand([id[0], id[1]]))
+        })
+    }
+
+    /// Returns the [`Sender`] slot for the given command ID and channel.
+    ///
+    /// Returns [`cmd::Error::UnknownCommand`] if the [`Dispatcher`] does not contain the ID, or
+    /// [`cmd::Error::InvalidChannel`] if the channel does not exist.
+    #[doc(hidden)]
+    async fn sender(
+        &self,
+        id: &[u8],
+        channel: usize,
+    ) -> Result<&smol::lock::Mutex<Option<Sender>>, Error> {
+        self.get(id).await?.sender(channel).ok_or_else(|| {
+            log::warn!("{self} does not contain channel {channel}");
+            escalate(cmd::Error::InvalidChannel(channel))
+        })
     }
 
     /// Creates a new [`broadcast channel`][1].
@@ -81,61 +196,313 @@ impl<const CH: usize> Dispatcher<CH> {
     /// [1]: Provenance::New
     /// [2]: Provenance::Existing
     /// [4]: Dispatcher::new_receiver
-    pub(crate) async fn receiver(&self, id: &[u8], channel: usize) -> Provenance {
-        let mut opt = self.get(id).await.sender(channel).lock().await;
+    pub(crate) async fn receiver(&self, id: &[u8], channel: usize) -> Result<Provenance, Error> {
+        let mut opt = self.sender(id, channel).await?.lock().await;
         match &*opt {
-            None => Provenance::New(Self::insert(&mut opt)),
-            Some(existing) => Provenance::Existing(existing.new_receiver()),
+            None => Ok(Provenance::New(Self::insert(&mut opt))),
+            Some(existing) => Ok(Provenance::Existing(existing.new_receiver())),
         }
     }
 
     /// Returns a [`Receiver`] for the given command ID. Guarantees that the device is not currently
     /// executing the command for the given ID.
-    pub(crate) async fn new_receiver(&self, id: &[u8], channel: usize) -> Provenance {
+    ///
+    /// Returns [`cmd::Error::Timeout`] if a pending command does not complete within the
+    /// `timeout`.
+    pub(crate) async fn new_receiver(
+        &self,
+        id: &[u8],
+        channel: usize,
+        timeout: Option<Duration>,
+    ) -> Result<Provenance, Error> {
         log::debug!("NEW RECEIVER (requested) ID {id:02X?} CHANNEL {channel}");
         loop {
-            let rx = self.receiver(id, channel).await;
+            let rx = self.receiver(id, channel).await?;
             if rx.is_new() {
                 // Break out of the loop and return the new Receiver
                 log::debug!("NEW RECEIVER (success) ID {id:02X?} CHANNEL {channel}");
-                return rx;
+                return Ok(rx);
             } else {
                 // Wait for the pending command to complete. No need to read the response
                 log::debug!("NEW RECEIVER (waiting) ID {id:02X?} CHANNEL {channel}");
-                let _ = rx.receive().await;
+                self.receive(rx, id, channel, timeout).await?;
+            }
+        }
+    }
+
+    /// Consumes the [`Provenance`], returning the message received by the wrapped [`Receiver`].
+    ///
+    /// If no response arrives within the `timeout`, the [`Receiver`] is dropped and
+    /// [`cmd::Error::Timeout`] is returned. The [`Sender`] is also removed from the
+    /// [`Dispatcher`] unless other functions are still awaiting the response, so that the next
+    /// call sends a new command. Waits indefinitely if `timeout` is [`None`].
+    pub(crate) async fn receive(
+        &self,
+        rx: Provenance,
+        id: &[u8],
+        channel: usize,
+        timeout: Option<Duration>,
+    ) -> Result<Arc<[u8]>, Error> {
+        let Some(timeout) = timeout else {
+            return rx.receive().await;
+        };
+        let expired = async {
+            Timer::after(timeout).await;
+            None
+        };
+        match smol::future::or(async { Some(rx.receive().await) }, expired).await {
+            Some(result) => result,
+            None => {
+                log::warn!("{self} CHANNEL {channel} RECEIVE {id:02X?} (timeout) {timeout:?}");
+                self.release(id, channel).await?;
+                Err(escalate(cmd::Error::Timeout(timeout)))
             }
         }
     }
 
+    /// Removes the [`Sender`] for the given command ID from the [`Dispatcher`] if no functions are
+    /// awaiting the command response.
+    #[doc(hidden)]
+    pub(crate) async fn release(&self, id: &[u8], channel: usize) -> Result<(), Error> {
+        let mut opt = self.sender(id, channel).await?.lock().await;
+        if opt
+            .as_ref()
+            .is_some_and(|sender| sender.receiver_count() == 0)
+        {
+            log::debug!("{self} CHANNEL {channel} RELEASE {id:02X?}");
+            opt.take();
+        }
+        Ok(())
+    }
+
     /// Takes the [`Sender`] out of the [`Dispatcher`] if functions are awaiting the command
     /// response, leaving [`None`] in its place.
     ///
     /// Returns [`None`] if no functions are awaiting the command response.
     #[doc(hidden)]
-    pub(crate) async fn take(&self, id: &[u8], channel: usize) -> Option<Sender> {
-        self.get(id).await.sender(channel).lock().await.take()
+    pub(crate) async fn take(&self, id: &[u8], channel: usize) -> Result<Option<Sender>, Error> {
+        Ok(self.sender(id, channel).await?.lock().await.take())
     }
 
-    /// Returns the expected length (number of bytes) for the given command ID.
-    pub(crate) async fn length(&self, id: &[u8]) -> usize {
-        self.get(id).await.length
+    /// Drops every [`Sender`] in the [`Dispatcher`]. Any functions awaiting a command response
+    /// are woken with a closed channel.
+    ///
+    /// Used when the device is disconnected unexpectedly, so that pending waiters do not await
+    /// indefinitely. The status cache is also cleared, as it no longer reflects the device.
+    pub(crate) async fn disconnect(&self) {
+        log::debug!("{self} DISCONNECT (requested)");
+        for command in self.map.values() {
+            for sender in &command.senders {
+                sender.lock().await.take();
+            }
+        }
+        self.cache().fill(None);
+        log::debug!("{self} DISCONNECT (success)");
+    }
+
+    /// Starts recording every message sent and received by the host to a new session file at the
+    /// specified path. Replaces any existing recording.
+    pub(crate) fn record(&self, path: &Path) -> Result<(), io::Error> {
+        log::debug!("{self} RECORD (requested) {path:?}");
+        let recorder = Recorder::create(path, &self.serial_number)?;
+        self.recorder().replace(recorder);
+        log::debug!("{self} RECORD (success)");
+        Ok(())
+    }
+
+    /// Stops recording. No action is taken if the [`Dispatcher`] is not recording.
+    pub(crate) fn stop_recording(&self) {
+        log::debug!("{self} STOP_RECORDING");
+        self.recorder().take();
+    }
+
+    /// Writes the message to the session file if the [`Dispatcher`] is recording.
+    pub(crate) fn capture(&self, direction: Direction, bytes: &[u8]) {
+        if let Some(recorder) = self.recorder().as_mut() {
+            recorder.write(direction, bytes);
+        }
+    }
+
+    /// Returns a [`MutexGuard`][1] protecting access to the optional [`Recorder`].
+    ///
+    /// ### Panics
+    ///
+    /// Calls [`bug_abort`] if the mutex is poisoned.
+    ///
+    /// [1]: std::sync::MutexGuard
+    #[doc(hidden)]
+    fn recorder(&self) -> std::sync::MutexGuard<'_, Option<Recorder>> {
+        self.recorder
+            .lock()
+            .unwrap_or_else(|e| bug_abort(format!("{self} recorder mutex is poisoned : {e}")))
     }
 
-    /// [`Broadcasts`][1] the command response to any waiting receivers.
+    /// [`Broadcasts`][1] the command response to any waiting receivers on the [`channel`][2]
+    /// that the message refers to.
+    ///
+    /// A copy of every message is also broadcast to [`events`][4] subscribers. Messages with an
+    /// unknown ID or channel, or with a length that does not match the expected length for the ID,
+    /// are passed to [`unhandled`][3] instead.
     ///
     /// [1]: Sender::broadcast_direct
-    pub(crate) async fn dispatch(&self, data: Arc<[u8]>, channel: usize) {
-        let id: &[u8] = &data[..2];
-        if let Some(sender) = self.take(id, channel).await {
+    /// [2]: Addressing::channel
+    /// [3]: Dispatcher::unhandled
+    /// [4]: Dispatcher::events
+    pub(crate) async fn dispatch(&self, data: Arc<[u8]>) {
+        self.capture(Direction::Rx, &data);
+        let id = [data[0], data[1]]; // Copying is more efficient than borrowing for u8
+        let channel = match (CH, self.addressing) {
+            // Generic single-channel devices do not always report a valid channel ident
+            (1, Addressing::Generic) => 1,
+            // Messages which are too short to contain a channel ident are unhandled
+            _ => self.addressing.channel(&data).unwrap_or(0),
+        };
+        if self.events.receiver_count() > 0 {
+            // Sender::try_broadcast returns an error if every subscriber was dropped meanwhile
+            let _ = self.events.try_broadcast(Event::new(channel, data.clone()));
+        }
+        if matches!(
+            id,
+            MOT_GET_USTATUSUPDATE | MOT_MOVE_COMPLETED | MOT_MOVE_STOPPED
+        ) && let Ok(status) = UStatus::decode(&data)
+        {
+            let now = Instant::now();
+            if let Some(entry) = self.cache().get_mut(channel.wrapping_sub(1)) {
+                entry.replace((now, status));
+            }
+            if id == MOT_GET_USTATUSUPDATE && self.updates.receiver_count() > 0 {
+                let _ = self.updates.try_broadcast((now, channel, status));
+            }
+        }
+        if let Some(fault) = DeviceFault::decode(&data) {
+            return self.fault(fault, data).await;
+        }
+        let Some(command) = self.map.get(&id) else {
+            return self.unhandled(data, channel);
+        };
+        if command.length != data.len() {
+            log::warn!(
+                "{self} CHANNEL {channel} DISPATCH {id:02X?} (invalid length) EXPECTED {} \
+                 RECEIVED {}",
+                command.length,
+                data.len(),
+            );
+            return self.unhandled(data, channel);
+        }
+        if command.sender(channel).is_none() {
+            return self.unhandled(data, channel);
+        }
+        if let Ok(Some(sender)) = self.take(&id, channel).await {
             // Sender::broadcast returns an error if either:
             //  1. The channel is closed
             //  2. The channel has no active receivers & Sender::await_active is False
-            sender
-                .broadcast_direct(data)
-                .await
-                .unwrap_or_else(|e| bug_abort(format!("Broadcast failed : {}", e)));
+            // Both occur if every receiver timed out before the response arrived.
+            if let Err(e) = sender.broadcast_direct(data).await {
+                log::debug!("{self} CHANNEL {channel} DISPATCH {id:02X?} (no receivers) {e}");
+            }
         }
     }
+
+    /// Broadcasts a [`DeviceFault`] reported by the device to every [`faults`][1] subscriber.
+    ///
+    /// Every function awaiting a command response on the channel that the fault refers to is
+    /// woken with the fault message, and returns [`cmd::Error::Fault`]. Faults which refer to the
+    /// whole device wake every waiting function.
+    ///
+    /// [1]: Dispatcher::faults
+    #[doc(hidden)]
+    async fn fault(&self, fault: DeviceFault, data: Arc<[u8]>) {
+        log::error!("{self} FAULT {fault}");
+        // Sender::try_broadcast returns an error if the fault has no subscribers
+        let _ = self.faults.try_broadcast(fault.clone());
+        let relates = |ch: usize| fault.channel.is_none_or(|channel| channel == ch);
+        for command in self.map.values() {
+            for (i, sender) in command.senders.iter().enumerate() {
+                if !relates(i + 1) {
+                    continue;
+                }
+                if let Some(sender) = sender.lock().await.take() {
+                    // Receivers may have timed out. See Dispatcher::dispatch.
+                    let _ = sender.broadcast_direct(data.clone()).await; // Inexpensive Arc Clone
+                }
+            }
+        }
+    }
+
+    /// Returns a new subscriber to every [`DeviceFault`] reported by the device.
+    ///
+    /// Each subscriber buffers up to [`FAULTS_CAPACITY`] faults. If a subscriber falls behind, the
+    /// oldest faults are discarded.
+    pub(crate) fn faults(&self) -> async_broadcast::Receiver<DeviceFault> {
+        self.faults.new_receiver()
+    }
+
+    /// Returns a new subscriber to a copy of every message received from the device.
+    ///
+    /// Subscribers do not affect the delivery of command responses. Each subscriber buffers up to
+    /// [`EVENTS_CAPACITY`] events. If a subscriber falls behind, the oldest events are discarded.
+    pub(crate) fn events(&self) -> async_broadcast::Receiver<Event> {
+        self.events.new_receiver()
+    }
+
+    /// Returns a new subscriber to every `GET_U_STATUS_UPDATE` message received from the device.
+    ///
+    /// Each subscriber buffers up to [`UPDATES_CAPACITY`] updates. If a subscriber falls behind,
+    /// the oldest updates are discarded and the subscriber receives [`RecvError::Overflowed`][1].
+    ///
+    /// [1]: async_broadcast::RecvError::Overflowed
+    pub(crate) fn updates(&self) -> async_broadcast::Receiver<Update> {
+        self.updates.new_receiver()
+    }
+
+    /// Returns the most recent status reported by the device on the given channel, if it was
+    /// received within `max_age`.
+    ///
+    /// The cache is updated by every `GET_U_STATUS_UPDATE`, `MOVE_COMPLETED`, and `MOVE_STOPPED`
+    /// message. Returns [`None`] if the cached status is older than `max_age`, or if the channel
+    /// does not exist.
+    pub(crate) fn cached(&self, channel: usize, max_age: Duration) -> Option<UStatus> {
+        let (timestamp, status) = (*self.cache().get(channel.checked_sub(1)?)?)?;
+        (timestamp.elapsed() < max_age).then_some(status)
+    }
+
+    /// Returns a [`MutexGuard`][1] protecting access to the status [`Cache`].
+    ///
+    /// ### Panics
+    ///
+    /// Calls [`bug_abort`] if the mutex is poisoned.
+    ///
+    /// [1]: std::sync::MutexGuard
+    #[doc(hidden)]
+    fn cache(&self) -> std::sync::MutexGuard<'_, Cache<CH>> {
+        self.cache
+            .lock()
+            .unwrap_or_else(|e| bug_abort(format!("{self} cache mutex is poisoned : {e}")))
+    }
+
+    /// Returns `True` while update messages are enabled.
+    pub(crate) fn update_messages(&self) -> bool {
+        self.update_messages.load(Ordering::SeqCst)
+    }
+
+    /// Records whether update messages are enabled. While enabled, the `Communicator`
+    /// periodically acknowledges update messages so that the device continues to send them.
+    ///
+    /// The state persists when the device is closed and reopened.
+    pub(crate) fn set_update_messages(&self, enabled: bool) {
+        log::debug!("{self} UPDATE_MESSAGES {enabled}");
+        self.update_messages.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Sink for incoming messages that cannot be routed to a [`Command`]. For example, unexpected
+    /// error reports or update messages which the device does not register.
+    ///
+    /// The message is logged and discarded. Framing is unaffected because the message length is
+    /// read from the header.
+    #[doc(hidden)]
+    fn unhandled(&self, data: Arc<[u8]>, channel: usize) {
+        log::warn!("{self} CHANNEL {channel} UNHANDLED {data:02X?}");
+    }
 }
 
 impl<const CH: usize> Display for Dispatcher<CH> {
@@ -0,0 +1,43 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use std::sync::Arc;
+
+use crate::apt::{self, Message};
+
+/// A copy of a message received from a Thorlabs device.
+///
+/// Every incoming message is reported as an [`Event`], including update messages, responses to
+/// commands, notifications triggered by the front panel, and faults.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    /// The channel that the message refers to. Channels are numbered from one.
+    pub channel: usize,
+    /// The complete message, including the six-byte header.
+    pub bytes: Arc<[u8]>,
+    /// The decoded message, or [`None`] if the message is not supported by the [`apt`] module.
+    pub message: Option<Message>,
+}
+
+impl Event {
+    /// Constructs a new [`Event`], decoding the message where possible.
+    pub(crate) fn new(channel: usize, bytes: Arc<[u8]>) -> Self {
+        Self {
+            channel,
+            message: apt::decode(&bytes).ok(),
+            bytes,
+        }
+    }
+
+    /// Returns the two-byte message ID.
+    pub fn id(&self) -> [u8; 2] {
+        [self.bytes[0], self.bytes[1]]
+    }
+}
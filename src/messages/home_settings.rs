@@ -0,0 +1,29 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use crate::apt::{Direction, LimitSwitch};
+
+/// The homing parameters of a Thorlabs device channel, in real-world units.
+///
+/// See [`KDC101::get_home_params`][1] and [`KDC101::set_home_params`][2].
+///
+/// [1]: crate::devices::KDC101::get_home_params
+/// [2]: crate::devices::KDC101::set_home_params
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HomeSettings {
+    /// The direction of the homing move.
+    pub direction: Direction,
+    /// The hardware limit switch used as the home reference.
+    pub limit_switch: LimitSwitch,
+    /// The homing velocity (mm/s).
+    pub velocity: f64,
+    /// The distance of the home position from the limit switch (mm).
+    pub offset: f64,
+}
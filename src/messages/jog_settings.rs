@@ -0,0 +1,35 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use crate::apt::{JogMode, StopMode};
+
+/// The jog parameters of a Thorlabs device channel, in real-world units.
+///
+/// See [`KDC101::get_jog_params`][1] and [`KDC101::set_jog_params`][2].
+///
+/// [1]: crate::devices::KDC101::get_jog_params
+/// [2]: crate::devices::KDC101::set_jog_params
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JogSettings {
+    /// Whether jog moves are continuous or single steps.
+    pub mode: JogMode,
+    /// The step size (mm). Used in [`Step`][1] mode.
+    ///
+    /// [1]: JogMode::Step
+    pub step_size: f64,
+    /// The maximum velocity (mm/s).
+    pub velocity: f64,
+    /// The acceleration (mm/s²).
+    pub acceleration: f64,
+    /// How a [`Continuous`][1] jog move stops.
+    ///
+    /// [1]: JogMode::Continuous
+    pub stop_mode: StopMode,
+}
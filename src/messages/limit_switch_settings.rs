@@ -0,0 +1,35 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use crate::apt::{HardLimit, SoftLimitMode};
+
+/// The limit switch parameters of a Thorlabs device channel, in real-world units.
+///
+/// See [`KDC101::get_limit_switch_params`][1] and [`KDC101::set_limit_switch_params`][2].
+///
+/// [1]: crate::devices::KDC101::get_limit_switch_params
+/// [2]: crate::devices::KDC101::set_limit_switch_params
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LimitSwitchSettings {
+    /// The operation of the forward (clockwise) hardware limit switch.
+    pub cw_hard_limit: HardLimit,
+    /// The operation of the reverse (counter-clockwise) hardware limit switch.
+    pub ccw_hard_limit: HardLimit,
+    /// `True` if the forward and reverse hardware limit switches are swapped.
+    pub swapped: bool,
+    /// The forward (clockwise) software limit position (mm).
+    pub cw_soft_limit: f64,
+    /// The reverse (counter-clockwise) software limit position (mm).
+    pub ccw_soft_limit: f64,
+    /// The action taken when a software limit is reached.
+    pub soft_limit_mode: SoftLimitMode,
+    /// `True` if the software limits are applied as for a rotation stage.
+    pub rotation: bool,
+}
@@ -10,16 +10,28 @@ modification, are permitted provided that the conditions of the LICENSE are met.
 
 /* ------------------------------------------------------------------------------ Public Modules */
 
-pub(crate) mod utils;
+pub(crate) mod session;
 
 /* ----------------------------------------------------------------------------- Private Modules */
 
 mod command;
 mod dispatcher;
+mod event;
+mod home_settings;
+mod jog_settings;
+mod limit_switch_settings;
 mod provenance;
+mod status_update;
 
 /* --------------------------------------------------------------------------- Public Re-Exports */
 
+pub use command::Metadata;
+pub use event::Event;
+pub use home_settings::HomeSettings;
+pub use jog_settings::JogSettings;
+pub use limit_switch_settings::LimitSwitchSettings;
+pub use status_update::StatusUpdate;
+
 /// A sender for broadcasting command responses to multiple receivers.
 pub type Sender = async_broadcast::Sender<std::sync::Arc<[u8]>>;
 
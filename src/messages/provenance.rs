@@ -10,7 +10,8 @@ modification, are permitted provided that the conditions of the LICENSE are met.
 
 use std::sync::Arc;
 
-use crate::devices::bug_abort;
+use crate::devices::escalate;
+use crate::error::{DeviceFault, Error, cmd};
 use crate::messages::Receiver;
 
 /// Indicates whether the wrapped [`Receiver`] is bound to a [`New`][1] or [`Existing`][2]
@@ -67,12 +68,20 @@ impl Provenance {
     }
 
     /// Consumes the [`Provenance`], returning the message received by the wrapped [`Receiver`].
-    pub(crate) async fn receive(self) -> Arc<[u8]> {
-        self.unpack().recv_direct().await.unwrap_or_else(|e| {
-            bug_abort(format!(
-                "Failed to receive command from broadcast channel : {}",
-                e
-            ))
-        })
+    ///
+    /// Returns [`cmd::Error::DeviceDisconnected`] if the device is [`disconnected`][1] before the
+    /// response arrives, or [`cmd::Error::Fault`] if the device reports a [`fault`][2] instead.
+    ///
+    /// [1]: crate::messages::Dispatcher::disconnect
+    /// [2]: crate::messages::Dispatcher::fault
+    pub(crate) async fn receive(self) -> Result<Arc<[u8]>, Error> {
+        let message = self.unpack().recv_direct().await.map_err(|e| {
+            log::warn!("Failed to receive command from broadcast channel : {}", e);
+            escalate(cmd::Error::DeviceDisconnected)
+        })?;
+        match DeviceFault::decode(&message) {
+            Some(fault) => Err(escalate(cmd::Error::Fault(fault))),
+            None => Ok(message),
+        }
     }
 }
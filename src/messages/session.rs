@@ -0,0 +1,137 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The direction of a recorded message, relative to the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Direction {
+    /// Bytes sent from the host to the device.
+    Tx,
+    /// A framed message received from the device.
+    Rx,
+    /// A keep-alive message sent from the host to the device. See [`Transport::keep_alive`][1].
+    ///
+    /// [1]: crate::traits::Transport::keep_alive
+    KeepAlive,
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::Tx => write!(f, "TX"),
+            Direction::Rx => write!(f, "RX"),
+            Direction::KeepAlive => write!(f, "KA"),
+        }
+    }
+}
+
+/// A single timestamped message in a recorded session file.
+#[derive(Debug, Clone)]
+pub(crate) struct Entry {
+    /// Time since the start of the recording.
+    pub(crate) time: Duration,
+    /// Whether the message was sent or received by the host.
+    pub(crate) direction: Direction,
+    /// The raw message bytes.
+    pub(crate) bytes: Vec<u8>,
+}
+
+impl Entry {
+    /// Parses a single line from a session file.
+    ///
+    /// Returns [`None`] for blank lines and `#` comments.
+    fn parse(line: &str) -> Option<io::Result<Self>> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("{line:?}"));
+        let mut words = line.split_whitespace();
+        let entry = (|| {
+            let time = words.next()?.parse::<f64>().ok()?;
+            let direction = match words.next()? {
+                "TX" => Direction::Tx,
+                "RX" => Direction::Rx,
+                "KA" => Direction::KeepAlive,
+                _ => return None,
+            };
+            let bytes = words
+                .map(|word| u8::from_str_radix(word, 16).ok())
+                .collect::<Option<Vec<u8>>>()?;
+            Some(Self {
+                time: Duration::try_from_secs_f64(time).ok()?,
+                direction,
+                bytes,
+            })
+        })();
+        Some(entry.ok_or_else(invalid))
+    }
+}
+
+/// Reads every [`Entry`] from a session file written by a [`Recorder`].
+pub(crate) fn read(path: &Path) -> io::Result<Vec<Entry>> {
+    BufReader::new(File::open(path)?)
+        .lines()
+        .filter_map(|line| match line {
+            Ok(line) => Entry::parse(&line),
+            Err(e) => Some(Err(e)),
+        })
+        .collect()
+}
+
+/// Writes a timestamped record of every message sent and received by the host to a session file.
+///
+/// Each line contains the time since the start of the recording (seconds), the [`Direction`], and
+/// the message bytes as hexadecimal. For example:
+///
+/// ```text
+/// 0.012345 TX 11 02 01 00 50 01
+/// 0.023456 RX 12 02 01 01 01 50
+/// 1.034567 KA 92 04 00 00 50 01
+/// ```
+#[derive(Debug)]
+pub(crate) struct Recorder {
+    /// The time at which the recording started.
+    start: Instant,
+    /// The session file. Each line is flushed as soon as it is written.
+    file: LineWriter<File>,
+}
+
+impl Recorder {
+    /// Creates a new session file at the specified path, overwriting any existing file.
+    pub(crate) fn create(path: &Path, serial_number: &str) -> io::Result<Self> {
+        let mut file = LineWriter::new(File::create(path)?);
+        let epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        writeln!(file, "# thormotion session {serial_number} started {epoch:.6}")?;
+        Ok(Self {
+            start: Instant::now(),
+            file,
+        })
+    }
+
+    /// Appends a timestamped message to the session file.
+    ///
+    /// Write errors are logged but otherwise ignored, so that a full disk does not interrupt
+    /// communication with the device.
+    pub(crate) fn write(&mut self, direction: Direction, bytes: &[u8]) {
+        let time = self.start.elapsed().as_secs_f64();
+        let hex: Vec<String> = bytes.iter().map(|byte| format!("{byte:02X}")).collect();
+        writeln!(self.file, "{time:.6} {direction} {}", hex.join(" "))
+            .unwrap_or_else(|e| log::error!("RECORDER WRITE (failed) {e}"));
+    }
+}
@@ -0,0 +1,33 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use std::time::Instant;
+
+/// A status update reported by a Thorlabs device, converted into real-world units.
+///
+/// See [`KDC101::status_stream`][1].
+///
+/// [1]: crate::devices::KDC101::status_stream
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatusUpdate {
+    /// The channel that the update refers to. Channels are numbered from one.
+    pub channel: usize,
+    /// The position (mm).
+    pub position: f64,
+    /// The velocity (mm/s).
+    pub velocity: f64,
+    /// The status bits (Thorlabs APT Protocol, Issue 39, Page 126).
+    pub status_bits: u32,
+    /// The time at which the host received the update.
+    pub timestamp: Instant,
+    /// The number of updates discarded immediately before this update because the consumer fell
+    /// behind.
+    pub missed: u64,
+}
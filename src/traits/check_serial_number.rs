@@ -19,14 +19,14 @@ pub(crate) trait CheckSerialNumber {
     /// 1. Does not match the serial number prefix for the target device type
     /// 2. Is not exactly eight-digits long
     /// 3. Contains non-numeric characters
-    fn check_serial_number(serial_number: &String) -> Result<(), Error> {
+    fn check_serial_number(serial_number: &str) -> Result<(), Error> {
         if serial_number.starts_with(Self::SERIAL_NUMBER_PREFIX)
             && serial_number.len() == 8
             && serial_number.chars().all(|c| c.is_numeric())
         {
             Ok(())
         } else {
-            Err(Error::Invalid(serial_number.clone()))
+            Err(Error::Invalid(serial_number.to_string()))
         }
     }
 }
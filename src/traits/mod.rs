@@ -12,6 +12,7 @@ modification, are permitted provided that the conditions of the LICENSE are met.
 
 mod check_serial_number;
 mod thorlabs_device;
+mod transport;
 mod unit_conversion;
 
 /* ----------------------------------------------------------------------------- Private Exports */
@@ -22,3 +23,4 @@ pub(crate) use unit_conversion::{UnitConversion, Units};
 /* ------------------------------------------------------------------------------ Public Exports */
 
 pub use thorlabs_device::ThorlabsDevice;
+pub use transport::{BoxFuture, Incoming, Transport};
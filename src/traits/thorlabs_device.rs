@@ -10,8 +10,11 @@ modification, are permitted provided that the conditions of the LICENSE are met.
 
 use std::fmt::{Debug, Display};
 use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 use crate::devices::UsbPrimitive;
+use crate::error::Error;
+use crate::functions;
 
 pub trait ThorlabsDevice<const CH: usize>: Debug + Display + Send + Sync {
     /// Returns a borrow that dereferences to the inner [`UsbPrimitive`]
@@ -42,6 +45,50 @@ pub trait ThorlabsDevice<const CH: usize>: Debug + Display + Send + Sync {
     /// [6]: crate::devices::utils::DEVICES
     /// [7]: ahash::HashMap
     fn abort(&self);
+
+    #[doc = include_str!("../documentation/send_raw.md")]
+    fn send_raw_async(&self, bytes: Vec<u8>) -> impl Future<Output = Result<(), Error>> + Send
+    where
+        Self: Sized,
+    {
+        functions::send_raw(self, bytes)
+    }
+
+    #[doc = include_str!("../documentation/send_raw.md")]
+    fn send_raw(&self, bytes: Vec<u8>) -> Result<(), Error>
+    where
+        Self: Sized,
+    {
+        smol::block_on(self.send_raw_async(bytes))
+    }
+
+    #[doc = include_str!("../documentation/request_raw.md")]
+    fn request_raw_async(
+        &self,
+        req_id: [u8; 2],
+        get_id: [u8; 2],
+        channel: usize,
+        payload: &[u8],
+    ) -> impl Future<Output = Result<Arc<[u8]>, Error>> + Send
+    where
+        Self: Sized,
+    {
+        functions::request_raw(self, req_id, get_id, channel, payload)
+    }
+
+    #[doc = include_str!("../documentation/request_raw.md")]
+    fn request_raw(
+        &self,
+        req_id: [u8; 2],
+        get_id: [u8; 2],
+        channel: usize,
+        payload: &[u8],
+    ) -> Result<Arc<[u8]>, Error>
+    where
+        Self: Sized,
+    {
+        smol::block_on(self.request_raw_async(req_id, get_id, channel, payload))
+    }
 }
 
 impl<const CH: usize> Hash for dyn ThorlabsDevice<CH> {
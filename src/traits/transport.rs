@@ -0,0 +1,73 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use std::fmt::Debug;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+
+/// A boxed [`Future`] returned by [`Transport`] functions.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A stream of raw bytes received from the device.
+///
+/// Each item contains one or more bytes in the order that they were received. Items are not
+/// required to align with message boundaries. Framing is handled by the [`Dispatcher`][1].
+///
+/// [1]: crate::messages::Dispatcher
+pub type Incoming = smol::stream::Boxed<io::Result<Vec<u8>>>;
+
+/// A bidirectional byte link between the host and a Thorlabs device.
+///
+/// The [`Transport`] is only responsible for moving raw bytes. Message framing, routing, and all
+/// device functions are implemented on top of the [`Transport`] by thormotion. This allows
+/// alternative links (e.g. mock devices, serial ports, network bridges) to be used without
+/// modifying the device functions.
+///
+/// The default [`Usb`][1] transport communicates with the FTDI USB-to-serial chip inside each
+/// Thorlabs device.
+///
+/// [1]: crate::transports::Usb
+pub trait Transport: Debug + Send + 'static {
+    /// Opens the link to the device and returns a stream of [`Incoming`] bytes.
+    ///
+    /// The stream should end, or yield an error, if the link is lost.
+    fn open(&mut self) -> BoxFuture<'_, io::Result<Incoming>>;
+
+    /// Closes the link to the device.
+    ///
+    /// The [`Incoming`] stream returned by [`open`][1] is dropped before this function is called.
+    ///
+    /// [1]: Transport::open
+    fn close(&mut self) -> BoxFuture<'_, io::Result<()>>;
+
+    /// Sends raw bytes to the device.
+    fn send(&mut self, bytes: Vec<u8>) -> BoxFuture<'_, io::Result<()>>;
+
+    /// Sends a keep-alive message to the device. Keep-alive messages are sent periodically while
+    /// update messages are enabled, so their timing is not deterministic.
+    ///
+    /// The default implementation calls [`send`][1]. Transports which do not communicate with a
+    /// real device may ignore keep-alive messages.
+    ///
+    /// [1]: Transport::send
+    fn keep_alive(&mut self, bytes: Vec<u8>) -> BoxFuture<'_, io::Result<()>> {
+        self.send(bytes)
+    }
+
+    /// Waits until the device is available again after the link was lost. The device is then
+    /// automatically reopened.
+    ///
+    /// The default implementation returns [`io::ErrorKind::Unsupported`], in which case the device
+    /// remains disconnected until it is explicitly reopened.
+    fn reconnect(&mut self) -> BoxFuture<'_, io::Result<()>> {
+        Box::pin(async { Err(io::ErrorKind::Unsupported.into()) })
+    }
+}
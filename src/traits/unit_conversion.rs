@@ -132,7 +132,7 @@ pub(crate) trait UnitConversion {
     /// [1]: UnitConversion::VELOCITY_SCALE_FACTOR
     fn velocity_from_f64(velocity: f64) -> Units {
         let bytes = Units::encode(velocity, Self::VELOCITY_SCALE_FACTOR);
-        Units::Distance(bytes)
+        Units::Velocity(bytes)
     }
 
     /// Converts an acceleration from real-world units (mm/s²) to device units using the appropriate
@@ -141,7 +141,7 @@ pub(crate) trait UnitConversion {
     /// [1]: UnitConversion::ACCELERATION_SCALE_FACTOR
     fn acceleration_from_f64(acceleration: f64) -> Units {
         let bytes = Units::encode(acceleration, Self::ACCELERATION_SCALE_FACTOR);
-        Units::Distance(bytes)
+        Units::Acceleration(bytes)
     }
 
     /// Consumes the [`Units`] enum, returning real-world units (millimeters and seconds) using the
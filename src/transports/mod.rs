@@ -0,0 +1,29 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+/* ----------------------------------------------------------------------------- Private Modules */
+
+mod replay;
+#[cfg(unix)]
+mod serial;
+mod simulator;
+mod tcp;
+mod usb;
+
+/* ------------------------------------------------------------------------------ Public Exports */
+
+pub use replay::Replay;
+#[cfg(unix)]
+pub use serial::Serial;
+pub use simulator::Simulator;
+pub use tcp::Tcp;
+pub use usb::{FtdiDiagnostics, FtdiStatus, Usb};
+
+pub use crate::traits::{BoxFuture, Incoming, Transport};
@@ -0,0 +1,164 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter};
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use smol::channel::{Receiver, Sender, unbounded};
+use smol::stream::StreamExt;
+use smol::{Task, Timer};
+
+use crate::messages::session::{Direction, Entry, read};
+use crate::traits::{BoxFuture, Incoming, Transport};
+
+/// A [`Transport`] that replays a session file recorded by a Thorlabs device.
+///
+/// Every command sent by the host must match the next `TX` entry in the session. The `RX` entries
+/// that follow are then delivered to the host with their recorded delays, as if the hardware were
+/// attached. A mismatched command returns [`io::ErrorKind::InvalidData`]. Keep-alive messages are
+/// not replayed, because their timing is not deterministic. Recorded `KA` entries are skipped, and
+/// keep-alive messages sent by the host are ignored. See [`Transport::keep_alive`].
+///
+/// Record a session using [`record`][1], then replay it using [`with_transport`][2].
+///
+/// ```no_run
+/// use thormotion::devices::KDC101;
+/// use thormotion::transports::Replay;
+///
+/// let replay = Replay::from_file("session.txt").unwrap();
+/// let mut device = KDC101::with_transport("27000001", replay).unwrap();
+/// device.open().unwrap();
+/// ```
+///
+/// [1]: crate::devices::KDC101::record
+/// [2]: crate::devices::KDC101::with_transport
+pub struct Replay {
+    /// Remaining entries in the session.
+    entries: VecDeque<Entry>,
+    /// Time of the most recent `TX` entry.
+    time: Duration,
+    /// A sender for scheduling `RX` entries. Contains [`None`] if the transport is closed.
+    schedule: Option<Sender<(Instant, Vec<u8>)>>,
+    /// An async background task that delivers scheduled `RX` entries. Contains [`None`] if the
+    /// transport is closed.
+    task: Option<Task<()>>,
+}
+
+impl Replay {
+    /// Constructs a new [`Replay`] transport from a session file.
+    pub fn from_file<P>(path: P) -> Result<Self, io::Error>
+    where
+        P: AsRef<Path>,
+    {
+        let entries = read(path.as_ref())?
+            .into_iter()
+            .filter(|entry| entry.direction != Direction::KeepAlive);
+        Ok(Self {
+            entries: entries.collect(),
+            time: Duration::ZERO,
+            schedule: None,
+            task: None,
+        })
+    }
+
+    /// Schedules every `RX` entry up to the next `TX` entry for delivery. Delays are measured
+    /// relative to the most recent `TX` entry.
+    fn schedule(&mut self) -> io::Result<()> {
+        let schedule = self.schedule.as_ref().ok_or_else(Self::closed)?;
+        let now = Instant::now();
+        while let Some(entry) = self.entries.pop_front() {
+            if entry.direction == Direction::Tx {
+                self.entries.push_front(entry);
+                break;
+            }
+            let due = now + entry.time.saturating_sub(self.time);
+            schedule
+                .try_send((due, entry.bytes))
+                .map_err(|_| Self::closed())?;
+        }
+        Ok(())
+    }
+
+    /// Spawns an async background task that delivers scheduled `RX` entries when they are due.
+    fn spawn(schedule: Receiver<(Instant, Vec<u8>)>, tx: Sender<Vec<u8>>) -> Task<()> {
+        smol::spawn(async move {
+            while let Ok((due, bytes)) = schedule.recv().await {
+                Timer::at(due).await;
+                if tx.send(bytes).await.is_err() {
+                    return; // Transport closed
+                }
+            }
+        })
+    }
+
+    /// Returns an [`io::Error`] indicating that the transport is closed.
+    fn closed() -> io::Error {
+        io::Error::new(io::ErrorKind::NotConnected, "Replay closed")
+    }
+}
+
+impl Transport for Replay {
+    fn open(&mut self) -> BoxFuture<'_, io::Result<Incoming>> {
+        Box::pin(async move {
+            let (tx, rx) = unbounded();
+            let (schedule, scheduled) = unbounded();
+            self.task = Some(Self::spawn(scheduled, tx));
+            self.schedule = Some(schedule);
+            self.schedule()?; // Messages received before the first command
+            Ok(Box::pin(rx.map(Ok)) as Incoming)
+        })
+    }
+
+    fn close(&mut self) -> BoxFuture<'_, io::Result<()>> {
+        Box::pin(async move {
+            self.schedule = None;
+            if let Some(task) = self.task.take() {
+                task.cancel().await;
+            }
+            Ok(())
+        })
+    }
+
+    fn send(&mut self, bytes: Vec<u8>) -> BoxFuture<'_, io::Result<()>> {
+        Box::pin(async move {
+            let expected = self.entries.pop_front().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::UnexpectedEof, "Replay session has ended")
+            })?;
+            if expected.direction != Direction::Tx || expected.bytes != bytes {
+                log::error!(
+                    "REPLAY MISMATCH EXPECTED {:02X?} SENT {bytes:02X?}",
+                    expected.bytes
+                );
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Replay expected {:02X?} but host sent {bytes:02X?}",
+                        expected.bytes
+                    ),
+                ));
+            }
+            self.time = expected.time;
+            self.schedule()
+        })
+    }
+
+    fn keep_alive(&mut self, _: Vec<u8>) -> BoxFuture<'_, io::Result<()>> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+impl Debug for Replay {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "REPLAY {{ {} entries remaining }}", self.entries.len())
+    }
+}
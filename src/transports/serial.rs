@@ -0,0 +1,229 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use std::fmt::{Debug, Formatter};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use smol::io::{AsyncReadExt, AsyncWriteExt};
+use smol::{Async, Timer, stream};
+
+use crate::messages::CMD_LEN_MAX;
+use crate::traits::{BoxFuture, Incoming, Transport};
+
+/// Directory containing persistent symlinks to serial devices, named by their USB serial number.
+const BY_ID: &str = "/dev/serial/by-id";
+/// Directory describing each tty device registered with the kernel.
+const SYS_TTY: &str = "/sys/class/tty";
+
+/// A [`Transport`] for Thorlabs devices that are owned by a kernel serial driver (e.g. `ftdi_sio`).
+///
+/// Communicates through a tty device such as `/dev/ttyUSB0` or `/dev/serial/by-id/...`. The tty is
+/// configured with the same settings as the default [`Usb`][1] transport:
+/// - Baud rate 115200
+/// - Eight data bits
+/// - One stop bit
+/// - No parity
+/// - RTS/CTS flow control
+///
+/// This transport is useful when the kernel driver cannot be detached from the device.
+///
+/// ```no_run
+/// use thormotion::devices::KDC101;
+/// use thormotion::transports::Serial;
+///
+/// let serial = Serial::find("27000001").unwrap();
+/// let mut device = KDC101::with_transport("27000001", serial).unwrap();
+/// device.open().unwrap();
+/// ```
+///
+/// [1]: crate::transports::Usb
+pub struct Serial {
+    /// Path to the tty device.
+    path: PathBuf,
+    /// The open tty device. Contains [`None`] if the transport is closed.
+    file: Option<Arc<Async<File>>>,
+}
+
+impl Serial {
+    /// Constructs a new [`Serial`] transport for the tty device at the specified path.
+    pub fn new<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            path: path.into(),
+            file: None,
+        }
+    }
+
+    /// Constructs a new [`Serial`] transport for the Thorlabs device with the specified serial
+    /// number by searching `/dev/serial/by-id`.
+    ///
+    /// Entries are named `usb-<vendor>_<product>_<serial number>-if<interface>-port<port>`. The
+    /// whole serial number must match, so that `2700001` does not select the tty for `27000010`.
+    ///
+    /// Returns [`io::ErrorKind::NotFound`] if no matching tty device exists, or
+    /// [`io::ErrorKind::InvalidInput`] if more than one tty device matches.
+    pub fn find(serial_number: &str) -> Result<Self, io::Error> {
+        let pattern = format!("_{serial_number}-if");
+        let mut paths = std::fs::read_dir(BY_ID)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.contains(&pattern))
+            });
+        match (paths.next(), paths.next()) {
+            (Some(path), None) => Ok(Self::new(path)),
+            (Some(_), Some(_)) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Multiple tty devices found in {BY_ID} for serial number {serial_number}"),
+            )),
+            (None, _) => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No tty device found in {BY_ID} for serial number {serial_number}"),
+            )),
+        }
+    }
+
+    /// Configures the tty according to Thorlabs APT protocol requirements, then purges any stale
+    /// data from the receive and transmit buffers.
+    async fn init(file: &File) -> io::Result<()> {
+        let fd = file.as_raw_fd();
+        let check = |result: libc::c_int| match result {
+            -1 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        };
+        // SAFETY: `fd` is a valid open file descriptor for the lifetime of `file`, and `termios`
+        // is fully initialised by `tcgetattr` before use.
+        unsafe {
+            let mut termios: libc::termios = std::mem::zeroed();
+            check(libc::tcgetattr(fd, &mut termios))?;
+            libc::cfmakeraw(&mut termios);
+            check(libc::cfsetispeed(&mut termios, libc::B115200))?;
+            check(libc::cfsetospeed(&mut termios, libc::B115200))?;
+            termios.c_cflag &= !(libc::PARENB | libc::CSTOPB | libc::CSIZE);
+            termios.c_cflag |= libc::CS8 | libc::CRTSCTS | libc::CLOCAL | libc::CREAD;
+            termios.c_cc[libc::VMIN] = 1;
+            termios.c_cc[libc::VTIME] = 0;
+            check(libc::tcsetattr(fd, libc::TCSANOW, &termios))?;
+        }
+        Timer::after(Duration::from_millis(50)).await; // Pre-purge dwell 50 ms
+        // SAFETY: `fd` is a valid open file descriptor for the lifetime of `file`.
+        check(unsafe { libc::tcflush(fd, libc::TCIOFLUSH) })?;
+        Timer::after(Duration::from_millis(50)).await; // Post-purge dwell 50 ms
+        // Set the Request To Send (RTS) signal. Pseudo-terminals do not support modem control
+        // lines, so failure is not fatal.
+        let rts: libc::c_int = libc::TIOCM_RTS;
+        // SAFETY: `fd` is a valid open file descriptor and `rts` outlives the call.
+        if let Err(e) = check(unsafe { libc::ioctl(fd, libc::TIOCMBIS, &rts) }) {
+            log::warn!("SERIAL RTS (failed) {e}");
+        }
+        Ok(())
+    }
+
+    /// Returns a stream of bytes read from the tty device. The stream ends after yielding the
+    /// first read error, or when the tty device reports end-of-file.
+    fn listen(file: Arc<Async<File>>) -> Incoming {
+        let stream = stream::unfold(Some(file), |state| async move {
+            let file = state?;
+            let mut buf = vec![0u8; CMD_LEN_MAX];
+            match (&*file).read(&mut buf).await {
+                Ok(0) => None,
+                Ok(n) => {
+                    buf.truncate(n);
+                    Some((Ok(buf), Some(file)))
+                }
+                Err(e) => Some((Err(e), None)),
+            }
+        });
+        Box::pin(stream)
+    }
+
+    /// Returns the path to the tty device.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the USB serial number of the device behind the tty.
+    ///
+    /// Entries in `/dev/serial/by-id` are named after the serial number. Other paths, such as
+    /// `/dev/ttyUSB0`, are looked up in `/sys/class/tty`.
+    ///
+    /// Returns [`io::ErrorKind::NotFound`] if the tty does not belong to a USB device.
+    pub fn serial_number(&self) -> Result<String, io::Error> {
+        let name = |path: &Path| path.file_name()?.to_str().map(String::from);
+        // Entries are named usb-<vendor>_<product>_<serial number>-if<interface>-port<port>
+        let by_id = name(&self.path).and_then(|name| {
+            let (head, _) = name.rsplit_once("-if")?;
+            let (_, serial_number) = head.rsplit_once('_')?;
+            Some(serial_number.to_string())
+        });
+        if let Some(serial_number) = by_id {
+            return Ok(serial_number);
+        }
+        let not_found = || {
+            let path = self.path.display();
+            io::Error::new(io::ErrorKind::NotFound, format!("{path} is not a USB tty device"))
+        };
+        let tty = name(&std::fs::canonicalize(&self.path)?).ok_or_else(not_found)?;
+        // The tty belongs to a USB interface, whose parent is the USB device
+        let path = Path::new(SYS_TTY).join(tty).join("device/../../serial");
+        let serial_number = std::fs::read_to_string(path).map_err(|_| not_found())?;
+        Ok(serial_number.trim().to_string())
+    }
+}
+
+impl Transport for Serial {
+    fn open(&mut self) -> BoxFuture<'_, io::Result<Incoming>> {
+        Box::pin(async move {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .custom_flags(libc::O_NOCTTY)
+                .open(&self.path)?;
+            Self::init(&file).await?;
+            let file = Arc::new(Async::new(file)?);
+            self.file = Some(file.clone());
+            Ok(Self::listen(file))
+        })
+    }
+
+    fn close(&mut self) -> BoxFuture<'_, io::Result<()>> {
+        Box::pin(async move {
+            self.file = None; // The tty is closed once the incoming stream is dropped
+            Ok(())
+        })
+    }
+
+    fn send(&mut self, bytes: Vec<u8>) -> BoxFuture<'_, io::Result<()>> {
+        Box::pin(async move {
+            let file = self
+                .file
+                .as_ref()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "Serial closed"))?;
+            (&**file).write_all(&bytes).await?;
+            (&**file).flush().await
+        })
+    }
+}
+
+impl Debug for Serial {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SERIAL {{ {} }}", self.path.display())
+    }
+}
@@ -0,0 +1,634 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use std::fmt::{Debug, Formatter};
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use smol::channel::{Sender, unbounded};
+use smol::stream::StreamExt;
+use smol::{Task, Timer};
+
+use crate::apt::{
+    self,
+    ChanEnableState,
+    DEVICE,
+    Direction,
+    HOST,
+    HardLimit,
+    HomeParams,
+    JogMode,
+    JogParams,
+    LimSwitchParams,
+    LimitSwitch,
+    Message,
+    Move,
+    SoftLimitMode,
+    StatusBits,
+    StopMode,
+    UStatus,
+    VelParams,
+};
+use crate::devices::bug_abort;
+use crate::traits::{BoxFuture, Incoming, Transport, UnitConversion};
+
+/// The simulated channel number. Simulated devices have one channel.
+const CHANNEL: u16 = 1;
+/// Total travel of the simulated stage (millimeters).
+const TRAVEL: f64 = 25.0;
+/// Default maximum velocity used for simulated moves (mm/s).
+const MAX_VELOCITY: f64 = 2.6;
+/// Maximum velocity accepted by the simulated stage (mm/s). Faster velocities are clamped.
+const VELOCITY_LIMIT: f64 = 2.6;
+/// Default acceleration reported by the simulated stage (mm/s²). Moves are simulated at constant
+/// velocity, so the acceleration is stored but not applied.
+const ACCELERATION: f64 = 1.5;
+/// Default step size used for simulated jog moves (millimeters).
+const JOG_STEP: f64 = 0.05;
+/// Default velocity used for simulated homing moves (mm/s).
+const HOME_VELOCITY: f64 = 2.0;
+/// Simulation time step.
+const TICK: Duration = Duration::from_millis(10);
+/// Interval between unsolicited GET_U_STATUS_UPDATE messages (10 Hz).
+const UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+/// Number of unsolicited status updates sent without a `MOT_ACK_USTATUSUPDATE` from the host,
+/// after which the simulated device stops sending updates.
+const UNACKNOWLEDGED_LIMIT: usize = 50;
+
+/// Status bits reported by the simulated stage (Thorlabs APT Protocol, Issue 39, Page 127).
+mod bits {
+    pub(super) const CW_HARD_LIMIT: u32 = 0x00000001;
+    pub(super) const CCW_HARD_LIMIT: u32 = 0x00000002;
+    pub(super) const MOVING_CW: u32 = 0x00000010;
+    pub(super) const MOVING_CCW: u32 = 0x00000020;
+    pub(super) const HOMING: u32 = 0x00000200;
+    pub(super) const HOMED: u32 = 0x00000400;
+    pub(super) const ENABLED: u32 = 0x80000000;
+}
+
+/// A [`Transport`] backed by an in-process virtual stage that speaks the Thorlabs APT protocol.
+///
+/// The virtual stage has a position, velocity, homed state, and limit switches at either end of
+/// its travel. Commands sent by the host are answered in the same way as a real device, including
+/// the `10 Hz` stream of unsolicited status updates after `START_UPDATE_MESSAGES`.
+///
+/// Construct a simulated device using [`KDC101::simulated`][1].
+///
+/// [1]: crate::devices::KDC101::simulated
+pub struct Simulator {
+    /// The shared state of the virtual stage.
+    stage: Arc<Mutex<Stage>>,
+    /// Incomplete bytes received from the host.
+    pending: Vec<u8>,
+    /// A sender for responses to the host. Contains [`None`] if the transport is closed.
+    tx: Option<Sender<Vec<u8>>>,
+    /// An async background task that advances the simulation. Contains [`None`] if the transport
+    /// is closed.
+    task: Option<Task<()>>,
+}
+
+impl Simulator {
+    /// Constructs a new [`Simulator`] using the unit conversion factors for device type `A`.
+    pub(crate) fn new<A>() -> Self
+    where
+        A: UnitConversion,
+    {
+        Self {
+            stage: Arc::new(Mutex::new(Stage::new::<A>())),
+            pending: Vec::new(),
+            tx: None,
+            task: None,
+        }
+    }
+
+    /// Returns a [`Plug`] handle, which simulates unplugging and reconnecting the device.
+    #[cfg(test)]
+    pub(crate) fn plug(&self) -> Plug {
+        Plug(self.stage.clone()) // Inexpensive Arc Clone
+    }
+
+    /// Returns an [`io::Error`] indicating that the simulated device is unplugged.
+    fn unplugged() -> io::Error {
+        io::Error::new(io::ErrorKind::NotConnected, "Simulator unplugged")
+    }
+
+    /// Locks the shared [`Stage`].
+    fn stage(&self) -> MutexGuard<'_, Stage> {
+        Self::lock(&self.stage)
+    }
+
+    /// Locks the shared [`Stage`].
+    ///
+    /// ### Panics
+    ///
+    /// Calls [`bug_abort`] if the mutex is poisoned.
+    fn lock(stage: &Mutex<Stage>) -> MutexGuard<'_, Stage> {
+        stage
+            .lock()
+            .unwrap_or_else(|e| bug_abort(format!("Simulator mutex is poisoned : {e}")))
+    }
+
+    /// Spawns an async background task that advances the simulation every [`TICK`] and sends any
+    /// resulting messages to the host.
+    fn spawn(stage: Arc<Mutex<Stage>>, tx: Sender<Vec<u8>>) -> Task<()> {
+        smol::spawn(async move {
+            let mut last = Instant::now();
+            loop {
+                Timer::after(TICK).await;
+                if !Self::lock(&stage).connected {
+                    tx.close(); // Ends the incoming stream
+                    return;
+                }
+                let now = Instant::now();
+                let messages = Self::lock(&stage).tick(now - last);
+                last = now;
+                for message in messages {
+                    if tx.send(message).await.is_err() {
+                        return; // Transport closed
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl Transport for Simulator {
+    fn open(&mut self) -> BoxFuture<'_, io::Result<Incoming>> {
+        Box::pin(async move {
+            if !self.stage().connected {
+                return Err(Self::unplugged());
+            }
+            let (tx, rx) = unbounded();
+            self.pending.clear();
+            self.task = Some(Self::spawn(self.stage.clone(), tx.clone()));
+            self.tx = Some(tx);
+            Ok(Box::pin(rx.map(Ok)) as Incoming)
+        })
+    }
+
+    fn close(&mut self) -> BoxFuture<'_, io::Result<()>> {
+        Box::pin(async move {
+            if let Some(task) = self.task.take() {
+                task.cancel().await;
+            }
+            self.tx = None;
+            self.stage().updates = false;
+            Ok(())
+        })
+    }
+
+    fn send(&mut self, bytes: Vec<u8>) -> BoxFuture<'_, io::Result<()>> {
+        Box::pin(async move {
+            let tx = self
+                .tx
+                .clone()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "Simulator closed"))?;
+            if !self.stage().connected {
+                return Err(Self::unplugged());
+            }
+            self.pending.extend(bytes);
+            while let Some(header) = self.pending.first_chunk::<6>() {
+                let len = apt::length(header);
+                if self.pending.len() < len {
+                    break;
+                }
+                let message: Vec<u8> = self.pending.drain(..len).collect();
+                let responses = self.stage().handle(&message);
+                for response in responses {
+                    tx.send(response)
+                        .await
+                        .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn reconnect(&mut self) -> BoxFuture<'_, io::Result<()>> {
+        Box::pin(async move {
+            while !self.stage().connected {
+                Timer::after(TICK).await;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Debug for Simulator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SIMULATOR {{ {:?} }}", self.stage())
+    }
+}
+
+/// A handle for simulating a USB cable being unplugged and reconnected.
+///
+/// While unplugged, the incoming stream ends and every command returns an [`io::Error`].
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub(crate) struct Plug(Arc<Mutex<Stage>>);
+
+#[cfg(test)]
+impl Plug {
+    /// Disconnects the simulated device.
+    pub(crate) fn unplug(&self) {
+        Simulator::lock(&self.0).connected = false;
+    }
+
+    /// Reconnects the simulated device.
+    pub(crate) fn replug(&self) {
+        Simulator::lock(&self.0).connected = true;
+    }
+}
+
+/// The kind of move currently being executed by the virtual stage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    /// A move towards a target position. Completes with `MOVE_COMPLETED`.
+    Move,
+    /// A jog move towards a target position. Completes with `MOVE_COMPLETED`.
+    Jog,
+    /// A homing move towards the configured limit switch. Completes with `MOVE_HOMED`.
+    Home,
+}
+
+/// A move currently being executed by the virtual stage.
+#[derive(Debug, Clone, Copy)]
+struct Motion {
+    /// The kind of move.
+    kind: Kind,
+    /// The target position (millimeters).
+    target: f64,
+    /// The move speed (mm/s).
+    speed: f64,
+}
+
+/// The state of the virtual stage.
+#[derive(Debug)]
+struct Stage {
+    /// Scale factor for converting millimeters to encoder counts.
+    distance_scale: f64,
+    /// Scale factor for converting mm/s to device velocity units.
+    velocity_scale: f64,
+    /// Scale factor for converting mm/s² to device acceleration units.
+    acceleration_scale: f64,
+    /// Current position (millimeters).
+    position: f64,
+    /// Current velocity (mm/s).
+    velocity: f64,
+    /// The move currently being executed, if any.
+    motion: Option<Motion>,
+    /// Maximum velocity used for moves (mm/s).
+    max_velocity: f64,
+    /// Stored acceleration (mm/s²).
+    acceleration: f64,
+    /// Stored jog parameters (device units).
+    jog: JogParams,
+    /// Stored homing parameters (device units).
+    home: HomeParams,
+    /// Stored limit switch parameters (device units).
+    limits: LimSwitchParams,
+    /// `True` if the stage has been homed.
+    homed: bool,
+    /// `True` if the channel is enabled.
+    enabled: bool,
+    /// `True` if unsolicited status update messages are enabled.
+    updates: bool,
+    /// Time since the last unsolicited status update message.
+    since_update: Duration,
+    /// Number of unsolicited status updates sent since the last `MOT_ACK_USTATUSUPDATE`.
+    unacknowledged: usize,
+    /// Stored absolute move position (millimeters). Used by `MOVE_ABSOLUTE` without a payload.
+    absolute: f64,
+    /// Stored relative move distance (millimeters). Used by `MOVE_RELATIVE` without a payload.
+    relative: f64,
+    /// `False` if the simulated device is unplugged. See [`Plug`].
+    connected: bool,
+}
+
+impl Stage {
+    /// Constructs a new un-homed [`Stage`] at the reverse end of its travel.
+    fn new<A>() -> Self
+    where
+        A: UnitConversion,
+    {
+        Self {
+            distance_scale: A::DISTANCE_ANGLE_SCALE_FACTOR,
+            velocity_scale: A::VELOCITY_SCALE_FACTOR,
+            acceleration_scale: A::ACCELERATION_SCALE_FACTOR,
+            position: 0.0,
+            velocity: 0.0,
+            motion: None,
+            max_velocity: MAX_VELOCITY,
+            acceleration: ACCELERATION,
+            jog: JogParams {
+                channel: CHANNEL,
+                mode: JogMode::Step,
+                step_size: (JOG_STEP * A::DISTANCE_ANGLE_SCALE_FACTOR).round() as i32,
+                min_velocity: 0,
+                acceleration: (ACCELERATION * A::ACCELERATION_SCALE_FACTOR).round() as i32,
+                max_velocity: (MAX_VELOCITY * A::VELOCITY_SCALE_FACTOR).round() as i32,
+                stop_mode: StopMode::Profiled,
+            },
+            home: HomeParams {
+                channel: CHANNEL,
+                direction: Direction::Reverse,
+                limit_switch: LimitSwitch::Reverse,
+                velocity: (HOME_VELOCITY * A::VELOCITY_SCALE_FACTOR).round() as i32,
+                offset: 0,
+            },
+            limits: LimSwitchParams {
+                channel: CHANNEL,
+                cw_hard_limit: HardLimit::Makes,
+                ccw_hard_limit: HardLimit::Makes,
+                swapped: false,
+                cw_soft_limit: (TRAVEL * A::DISTANCE_ANGLE_SCALE_FACTOR).round() as i32,
+                ccw_soft_limit: 0,
+                soft_limit_mode: SoftLimitMode::Ignore,
+                rotation: false,
+            },
+            homed: false,
+            enabled: true,
+            updates: false,
+            since_update: Duration::ZERO,
+            unacknowledged: 0,
+            absolute: 0.0,
+            relative: 0.0,
+            connected: true,
+        }
+    }
+
+    /// Returns the current status bits.
+    fn status_bits(&self) -> u32 {
+        let mut status = 0;
+        if self.position >= TRAVEL {
+            status |= bits::CW_HARD_LIMIT;
+        }
+        if self.position <= 0.0 {
+            status |= bits::CCW_HARD_LIMIT;
+        }
+        if let Some(motion) = self.motion {
+            if motion.target > self.position {
+                status |= bits::MOVING_CW;
+            } else if motion.target < self.position {
+                status |= bits::MOVING_CCW;
+            }
+            if motion.kind == Kind::Home {
+                status |= bits::HOMING;
+            }
+        }
+        if self.homed {
+            status |= bits::HOMED;
+        }
+        if self.enabled {
+            status |= bits::ENABLED;
+        }
+        status
+    }
+
+    /// Returns the status data packet shared by `GET_U_STATUS_UPDATE`, `MOVE_COMPLETED`, and
+    /// `MOVE_STOPPED`.
+    fn status(&self) -> UStatus {
+        UStatus {
+            channel: CHANNEL,
+            position: (self.position * self.distance_scale).round() as i32,
+            velocity: (self.velocity * self.velocity_scale)
+                .round()
+                .min(u16::MAX as f64) as u16,
+            current: 0,
+            status_bits: self.status_bits(),
+        }
+    }
+
+    /// Returns the velocity profile data packet.
+    fn vel_params(&self) -> VelParams {
+        VelParams {
+            channel: CHANNEL,
+            min_velocity: 0,
+            acceleration: (self.acceleration * self.acceleration_scale).round() as i32,
+            max_velocity: (self.max_velocity * self.velocity_scale).round() as i32,
+        }
+    }
+
+    /// Clamps a velocity (device units) to the [`VELOCITY_LIMIT`] of the simulated stage.
+    fn clamp(&self, velocity: i32) -> i32 {
+        let limit = (VELOCITY_LIMIT * self.velocity_scale).round() as i32;
+        velocity.clamp(-limit, limit)
+    }
+
+    /// Begins a jog move in the specified direction using the stored jog parameters.
+    fn jog(&mut self, direction: Direction) {
+        let distance = match self.jog.mode {
+            JogMode::Step => self.jog.step_size as f64 / self.distance_scale,
+            JogMode::Continuous => TRAVEL, // Stops at the limit switch
+        };
+        match direction {
+            Direction::Forward => self.start(Kind::Jog, self.position + distance),
+            Direction::Reverse => self.start(Kind::Jog, self.position - distance),
+        }
+    }
+
+    /// Returns the distance in millimeters described by the data packet.
+    fn distance(&self, data: Move) -> f64 {
+        data.distance as f64 / self.distance_scale
+    }
+
+    /// Begins a move towards the target position. The target is clamped to the limit switches,
+    /// and to the software limits unless they are ignored or the move is a homing move.
+    fn start(&mut self, kind: Kind, target: f64) {
+        let target = match (kind, self.limits.soft_limit_mode) {
+            (Kind::Home, _) | (_, SoftLimitMode::Ignore) => target,
+            _ => target
+                .max(self.limits.ccw_soft_limit as f64 / self.distance_scale)
+                .min(self.limits.cw_soft_limit as f64 / self.distance_scale),
+        };
+        let speed = match kind {
+            Kind::Move => self.max_velocity,
+            Kind::Jog => self.jog.max_velocity as f64 / self.velocity_scale,
+            Kind::Home => self.home.velocity as f64 / self.velocity_scale,
+        };
+        self.motion = Some(Motion {
+            kind,
+            target: target.clamp(0.0, TRAVEL),
+            speed,
+        });
+    }
+
+    /// Advances the simulation by the time step `dt`. Returns any messages for the host.
+    fn tick(&mut self, dt: Duration) -> Vec<Vec<u8>> {
+        let mut messages = Vec::new();
+        if let Some(motion) = self.motion {
+            let step = motion.speed * dt.as_secs_f64();
+            let remaining = motion.target - self.position;
+            if remaining.abs() <= step {
+                self.position = motion.target;
+                self.velocity = 0.0;
+                self.motion = None;
+                match motion.kind {
+                    Kind::Move | Kind::Jog => {
+                        messages.push(respond(Message::MotMoveCompleted(self.status())))
+                    }
+                    Kind::Home => {
+                        self.homed = true;
+                        messages.push(respond(Message::MotMoveHomed {
+                            channel: CHANNEL as u8,
+                        }));
+                    }
+                }
+            } else {
+                self.position += step.copysign(remaining);
+                self.velocity = motion.speed;
+            }
+        }
+        if self.updates && self.unacknowledged < UNACKNOWLEDGED_LIMIT {
+            self.since_update += dt;
+            if self.since_update >= UPDATE_INTERVAL {
+                self.since_update = Duration::ZERO;
+                self.unacknowledged += 1;
+                messages.push(respond(Message::MotGetUStatusUpdate(self.status())));
+            }
+        }
+        messages
+    }
+
+    /// Handles a single message from the host. Returns any immediate responses.
+    fn handle(&mut self, message: &[u8]) -> Vec<Vec<u8>> {
+        let decoded = match apt::decode(message) {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                log::warn!("SIMULATOR IGNORED {message:02X?} : {e}");
+                return vec![];
+            }
+        };
+        match decoded {
+            Message::ModSetChanEnableState(state) => {
+                self.enabled = state.enabled;
+                vec![]
+            }
+            Message::ModReqChanEnableState { .. } => {
+                vec![respond(Message::ModGetChanEnableState(ChanEnableState {
+                    channel: CHANNEL as u8,
+                    enabled: self.enabled,
+                }))]
+            }
+            Message::ModIdentify { .. } => vec![],
+            Message::HwStartUpdateMsgs => {
+                self.updates = true;
+                self.unacknowledged = 0;
+                vec![]
+            }
+            Message::HwStopUpdateMsgs => {
+                self.updates = false;
+                vec![]
+            }
+            Message::MotSetVelParams(params) => {
+                self.acceleration = params.acceleration as f64 / self.acceleration_scale;
+                self.max_velocity = self.clamp(params.max_velocity) as f64 / self.velocity_scale;
+                vec![]
+            }
+            Message::MotReqVelParams { .. } => {
+                vec![respond(Message::MotGetVelParams(self.vel_params()))]
+            }
+            Message::MotSetJogParams(params) => {
+                self.jog = JogParams {
+                    max_velocity: self.clamp(params.max_velocity),
+                    ..params
+                };
+                vec![]
+            }
+            Message::MotReqJogParams { .. } => {
+                vec![respond(Message::MotGetJogParams(self.jog))]
+            }
+            Message::MotSetLimSwitchParams(params) => {
+                self.limits = params;
+                vec![]
+            }
+            Message::MotReqLimSwitchParams { .. } => {
+                vec![respond(Message::MotGetLimSwitchParams(self.limits))]
+            }
+            Message::MotSetHomeParams(params) => {
+                self.home = HomeParams {
+                    velocity: self.clamp(params.velocity),
+                    ..params
+                };
+                vec![]
+            }
+            Message::MotReqHomeParams { .. } => {
+                vec![respond(Message::MotGetHomeParams(self.home))]
+            }
+            Message::MotMoveJog { direction, .. } => {
+                self.jog(direction);
+                vec![]
+            }
+            Message::MotReqStatusBits { .. } => {
+                vec![respond(Message::MotGetStatusBits(StatusBits {
+                    channel: CHANNEL,
+                    status_bits: self.status_bits(),
+                }))]
+            }
+            Message::MotAckUStatusUpdate => {
+                self.unacknowledged = 0;
+                vec![]
+            }
+            Message::MotReqUStatusUpdate { .. } => {
+                vec![respond(Message::MotGetUStatusUpdate(self.status()))]
+            }
+            Message::MotMoveHome { .. } => {
+                self.homed = false;
+                // Home towards the configured limit, then back off by the offset distance
+                let offset = self.home.offset as f64 / self.distance_scale;
+                let target = match self.home.direction {
+                    Direction::Forward => TRAVEL - offset,
+                    Direction::Reverse => offset,
+                };
+                self.start(Kind::Home, target);
+                vec![]
+            }
+            Message::MotSetMoveRelParams(data) => {
+                self.relative = self.distance(data);
+                vec![]
+            }
+            Message::MotMoveRelative(data) => {
+                self.start(Kind::Move, self.position + self.distance(data));
+                vec![]
+            }
+            Message::MotMoveRelativeFromParams { .. } => {
+                self.start(Kind::Move, self.position + self.relative);
+                vec![]
+            }
+            Message::MotSetMoveAbsParams(data) => {
+                self.absolute = self.distance(data);
+                vec![]
+            }
+            Message::MotMoveAbsolute(data) => {
+                self.start(Kind::Move, self.distance(data));
+                vec![]
+            }
+            Message::MotMoveAbsoluteFromParams { .. } => {
+                self.start(Kind::Move, self.absolute);
+                vec![]
+            }
+            Message::MotMoveStop { .. } => {
+                self.motion = None;
+                self.velocity = 0.0;
+                vec![respond(Message::MotMoveStopped(self.status()))]
+            }
+            _ => {
+                log::warn!("SIMULATOR IGNORED {message:02X?}");
+                vec![]
+            }
+        }
+    }
+}
+
+/// Encodes a message from the simulated device to the host.
+fn respond(message: Message) -> Vec<u8> {
+    apt::encode_addressed(&message, HOST, DEVICE)
+}
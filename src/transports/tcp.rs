@@ -0,0 +1,109 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use std::fmt::{Debug, Formatter};
+use std::io;
+
+use smol::io::{AsyncReadExt, AsyncWriteExt};
+use smol::net::TcpStream;
+use smol::stream;
+
+use crate::messages::CMD_LEN_MAX;
+use crate::traits::{BoxFuture, Incoming, Transport};
+
+/// A [`Transport`] that carries raw APT messages over a TCP socket.
+///
+/// Useful for Thorlabs devices that are exposed through a network serial bridge (e.g. `ser2net`).
+/// The bridge is responsible for configuring the serial port. Bytes are forwarded without any
+/// additional framing.
+///
+/// ```no_run
+/// use thormotion::devices::KDC101;
+/// use thormotion::transports::Tcp;
+///
+/// let tcp = Tcp::new("192.168.0.10:4001");
+/// let mut device = KDC101::with_transport("27000001", tcp).unwrap();
+/// device.open().unwrap();
+/// ```
+pub struct Tcp {
+    /// The `host:port` address of the network serial bridge.
+    address: String,
+    /// The connected socket. Contains [`None`] if the transport is closed.
+    stream: Option<TcpStream>,
+}
+
+impl Tcp {
+    /// Constructs a new [`Tcp`] transport for the network serial bridge at the specified
+    /// `host:port` address. The connection is established when the device is opened.
+    pub fn new<A>(address: A) -> Self
+    where
+        A: Into<String>,
+    {
+        Self {
+            address: address.into(),
+            stream: None,
+        }
+    }
+
+    /// Returns a stream of bytes read from the socket. The stream ends after yielding the first
+    /// read error, or when the remote end closes the connection.
+    fn listen(stream: TcpStream) -> Incoming {
+        let stream = stream::unfold(Some(stream), |state| async move {
+            let mut stream = state?;
+            let mut buf = vec![0u8; CMD_LEN_MAX];
+            match stream.read(&mut buf).await {
+                Ok(0) => None,
+                Ok(n) => {
+                    buf.truncate(n);
+                    Some((Ok(buf), Some(stream)))
+                }
+                Err(e) => Some((Err(e), None)),
+            }
+        });
+        Box::pin(stream)
+    }
+}
+
+impl Transport for Tcp {
+    fn open(&mut self) -> BoxFuture<'_, io::Result<Incoming>> {
+        Box::pin(async move {
+            let stream = TcpStream::connect(self.address.as_str()).await?;
+            stream.set_nodelay(true)?; // APT messages are small and latency sensitive
+            self.stream = Some(stream.clone()); // Inexpensive Arc Clone
+            Ok(Self::listen(stream))
+        })
+    }
+
+    fn close(&mut self) -> BoxFuture<'_, io::Result<()>> {
+        Box::pin(async move {
+            match self.stream.take().map(|s| s.shutdown(std::net::Shutdown::Both)) {
+                Some(Err(e)) if e.kind() != io::ErrorKind::NotConnected => Err(e),
+                _ => Ok(()), // The remote end may have already closed the connection
+            }
+        })
+    }
+
+    fn send(&mut self, bytes: Vec<u8>) -> BoxFuture<'_, io::Result<()>> {
+        Box::pin(async move {
+            let stream = self
+                .stream
+                .as_mut()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "TCP closed"))?;
+            stream.write_all(&bytes).await?;
+            stream.flush().await
+        })
+    }
+}
+
+impl Debug for Tcp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TCP {{ {} }}", self.address)
+    }
+}
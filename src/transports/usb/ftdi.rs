@@ -0,0 +1,221 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU16, AtomicU64, Ordering};
+
+/// Modem status bits (FTDI AN232B-04, Byte 0).
+mod modem {
+    pub(super) const CTS: u8 = 0x10;
+    pub(super) const DSR: u8 = 0x20;
+    pub(super) const RI: u8 = 0x40;
+    pub(super) const DCD: u8 = 0x80;
+}
+
+/// Line status bits (FTDI AN232B-04, Byte 1).
+mod line {
+    pub(super) const OVERRUN: u8 = 0x02;
+    pub(super) const PARITY: u8 = 0x04;
+    pub(super) const FRAMING: u8 = 0x08;
+    pub(super) const BREAK: u8 = 0x10;
+    pub(super) const FIFO: u8 = 0x80;
+    /// Every bit which indicates a receive error.
+    pub(super) const ERRORS: u8 = OVERRUN | PARITY | FRAMING | BREAK | FIFO;
+}
+
+/// The modem status and line status bytes reported by the FTDI chip.
+///
+/// The FTDI chip inside each Thorlabs device inserts these two bytes at the start of every USB
+/// packet sent to the host.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct FtdiStatus {
+    /// Modem status byte.
+    modem: u8,
+    /// Line status byte.
+    line: u8,
+}
+
+impl FtdiStatus {
+    /// Returns `True` if the Clear To Send (CTS) signal is active.
+    pub fn cts(&self) -> bool {
+        self.modem & modem::CTS != 0
+    }
+
+    /// Returns `True` if the Data Set Ready (DSR) signal is active.
+    pub fn dsr(&self) -> bool {
+        self.modem & modem::DSR != 0
+    }
+
+    /// Returns `True` if the Ring Indicator (RI) signal is active.
+    pub fn ri(&self) -> bool {
+        self.modem & modem::RI != 0
+    }
+
+    /// Returns `True` if the Data Carrier Detect (DCD) signal is active.
+    pub fn dcd(&self) -> bool {
+        self.modem & modem::DCD != 0
+    }
+
+    /// Returns `True` if the receive buffer overflowed and bytes were lost.
+    pub fn overrun_error(&self) -> bool {
+        self.line & line::OVERRUN != 0
+    }
+
+    /// Returns `True` if a byte was received with a parity error.
+    pub fn parity_error(&self) -> bool {
+        self.line & line::PARITY != 0
+    }
+
+    /// Returns `True` if a byte was received without a valid stop bit.
+    pub fn framing_error(&self) -> bool {
+        self.line & line::FRAMING != 0
+    }
+
+    /// Returns `True` if a break condition was received.
+    pub fn break_interrupt(&self) -> bool {
+        self.line & line::BREAK != 0
+    }
+
+    /// Returns `True` if any receive error bit is set.
+    pub fn is_error(&self) -> bool {
+        self.line & line::ERRORS != 0
+    }
+
+    /// Returns the raw modem status byte.
+    pub fn modem_status(&self) -> u8 {
+        self.modem
+    }
+
+    /// Returns the raw line status byte.
+    pub fn line_status(&self) -> u8 {
+        self.line
+    }
+}
+
+impl Debug for FtdiStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "FTDI STATUS {{ MODEM {:02X} LINE {:02X} }}",
+            self.modem, self.line
+        )
+    }
+}
+
+/// A handle for reading FTDI diagnostics from an open [`Usb`][1] transport.
+///
+/// Obtain a handle using [`Usb::diagnostics`][2] before passing the transport to a device. The
+/// handle is inexpensive to clone, and is updated as each USB packet is received.
+///
+/// [1]: crate::transports::Usb
+/// [2]: crate::transports::Usb::diagnostics
+#[derive(Clone, Default)]
+pub struct FtdiDiagnostics {
+    inner: Arc<Counters>,
+}
+
+/// Shared state behind a [`FtdiDiagnostics`] handle.
+#[derive(Default)]
+struct Counters {
+    /// The most recent modem status (high byte) and line status (low byte).
+    status: AtomicU16,
+    /// Number of packets with the overrun error bit set.
+    overrun: AtomicU64,
+    /// Number of packets with the parity error bit set.
+    parity: AtomicU64,
+    /// Number of packets with the framing error bit set.
+    framing: AtomicU64,
+    /// Number of packets with the break interrupt bit set.
+    breaks: AtomicU64,
+}
+
+impl FtdiDiagnostics {
+    /// Returns the [`FtdiStatus`] from the most recently received USB packet.
+    pub fn status(&self) -> FtdiStatus {
+        let [modem, line] = self.inner.status.load(Ordering::Relaxed).to_be_bytes();
+        FtdiStatus { modem, line }
+    }
+
+    /// Returns the number of USB packets which reported an overrun error.
+    pub fn overrun_errors(&self) -> u64 {
+        self.inner.overrun.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of USB packets which reported a parity error.
+    pub fn parity_errors(&self) -> u64 {
+        self.inner.parity.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of USB packets which reported a framing error.
+    pub fn framing_errors(&self) -> u64 {
+        self.inner.framing.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of USB packets which reported a break interrupt.
+    pub fn break_interrupts(&self) -> u64 {
+        self.inner.breaks.load(Ordering::Relaxed)
+    }
+
+    /// Records the [`FtdiStatus`] from a received USB packet.
+    fn record(&self, status: FtdiStatus) {
+        let inner = &self.inner;
+        inner.status.store(
+            u16::from_be_bytes([status.modem, status.line]),
+            Ordering::Relaxed,
+        );
+        if !status.is_error() {
+            return;
+        }
+        log::warn!("{status:?} (receive error)");
+        let count = |counter: &AtomicU64, set: bool| {
+            if set {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+        };
+        count(&inner.overrun, status.overrun_error());
+        count(&inner.parity, status.parity_error());
+        count(&inner.framing, status.framing_error());
+        count(&inner.breaks, status.break_interrupt());
+    }
+
+    /// Removes the two FTDI status bytes from the start of every `packet_size` chunk in a
+    /// completed bulk transfer, returning the remaining data bytes.
+    ///
+    /// A completed transfer may contain any number of packets. Every packet except the last is
+    /// exactly `packet_size` bytes long. The status bytes are recorded as diagnostics.
+    pub(crate) fn deframe(&self, transfer: &[u8], packet_size: usize) -> Vec<u8> {
+        let mut data = Vec::with_capacity(transfer.len());
+        for packet in transfer.chunks(packet_size) {
+            if let [modem, line, payload @ ..] = packet {
+                self.record(FtdiStatus {
+                    modem: *modem,
+                    line: *line,
+                });
+                data.extend_from_slice(payload);
+            }
+        }
+        data
+    }
+}
+
+impl Debug for FtdiDiagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "FTDI DIAGNOSTICS {{ {:?} OVERRUN {} PARITY {} FRAMING {} BREAK {} }}",
+            self.status(),
+            self.overrun_errors(),
+            self.parity_errors(),
+            self.framing_errors(),
+            self.break_interrupts(),
+        )
+    }
+}
@@ -0,0 +1,193 @@
+/*
+Project: thormotion
+GitHub: https://github.com/MillieFD/thormotion
+
+BSD 3-Clause License, Copyright (c) 2025, Amelia Fraser-Dale
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the conditions of the LICENSE are met.
+*/
+
+mod ftdi;
+mod serial_port;
+
+use std::fmt::{Debug, Formatter};
+use std::io;
+
+pub use ftdi::{FtdiDiagnostics, FtdiStatus};
+use nusb::hotplug::HotplugEvent;
+use nusb::transfer::{Buffer, Bulk, In, Out};
+use nusb::{DeviceInfo, Endpoint, list_devices, watch_devices};
+use smol::stream::{self, StreamExt};
+
+use crate::messages::CMD_LEN_MAX;
+use crate::traits::{BoxFuture, Incoming, Transport};
+
+/// The USB endpoint used for incoming commands from the device
+const IN_ENDPOINT: u8 = 0x81;
+/// The default number of concurrent transfers to maintain in the queue
+const QUEUE_DEPTH: usize = 3;
+/// The USB endpoint used for outgoing commands to the device
+const OUT_ENDPOINT: u8 = 0x02;
+
+/// The default [`Transport`] for Thorlabs devices.
+///
+/// Communicates with the FTDI USB-to-serial chip inside each Thorlabs device using the bulk
+/// endpoints of a claimed [`Interface`][1].
+///
+/// [1]: nusb::Interface
+pub struct Usb {
+    /// The serial number reported by the USB device. Used to find the device after it is
+    /// reconnected.
+    serial_number: String,
+    /// Information about a device that can be obtained without calling [`DeviceInfo::open`].
+    device_info: DeviceInfo,
+    /// An [`outgoing`][1] [`Bulk`] [`Endpoint`] for sending commands to the device.
+    ///
+    /// Contains [`None`] if the transport is closed.
+    ///
+    /// [1]: Out
+    outgoing: Option<Endpoint<Bulk, Out>>,
+    /// The number of concurrent transfers submitted to the [`incoming`][1] [`Bulk`] [`Endpoint`].
+    ///
+    /// [1]: In
+    queue_depth: usize,
+    /// Modem and line status reported by the FTDI chip.
+    diagnostics: FtdiDiagnostics,
+}
+
+impl Usb {
+    /// Constructs a new [`Usb`] transport for the specified USB device.
+    pub fn new(device_info: DeviceInfo) -> Self {
+        Self::with_queue_depth(device_info, QUEUE_DEPTH)
+    }
+
+    /// Constructs a new [`Usb`] transport for the specified USB device, which keeps `queue_depth`
+    /// incoming transfers pending at all times. The minimum queue depth is one.
+    ///
+    /// A deeper queue allows the host to absorb bursts of messages (e.g. status updates from
+    /// several channels) without the device waiting for a free transfer. The default is three.
+    pub fn with_queue_depth(device_info: DeviceInfo, queue_depth: usize) -> Self {
+        Self {
+            serial_number: device_info.serial_number().unwrap_or_default().to_string(),
+            device_info,
+            outgoing: None,
+            queue_depth: queue_depth.max(1),
+            diagnostics: FtdiDiagnostics::default(),
+        }
+    }
+
+    /// Returns a handle for reading the modem and line status reported by the FTDI chip.
+    ///
+    /// The handle remains valid after the transport is passed to a device, and across reconnects.
+    pub fn diagnostics(&self) -> FtdiDiagnostics {
+        self.diagnostics.clone() // Inexpensive Arc Clone
+    }
+
+    /// Returns a stream of bytes received from the [`incoming`][1] [`Bulk`] [`Endpoint`].
+    ///
+    /// The FTDI chip prefixes every packet with two status bytes. A single transfer may contain
+    /// several packets, so the status bytes are removed from each packet before the bytes are
+    /// yielded. The stream ends after yielding the first transfer error.
+    ///
+    /// [1]: In
+    fn listen(
+        mut endpoint: Endpoint<Bulk, In>,
+        queue_depth: usize,
+        diagnostics: FtdiDiagnostics,
+    ) -> Incoming {
+        // Buffer size must be a nonzero multiple of the endpoint's maximum packet size
+        let pkt_size = endpoint.max_packet_size();
+        let buf_size = CMD_LEN_MAX.div_ceil(pkt_size) * pkt_size;
+        while endpoint.pending() < queue_depth {
+            endpoint.submit(Buffer::new(buf_size));
+        }
+        let stream = stream::unfold(Some((endpoint, diagnostics)), move |state| async move {
+            let (mut endpoint, diagnostics) = state?;
+            loop {
+                let mut completion = endpoint.next_complete().await;
+                if let Err(e) = completion.status {
+                    return Some((Err(io::Error::from(e)), None));
+                }
+                let bytes = diagnostics.deframe(&completion.buffer, pkt_size); // Skip status bytes
+                completion.buffer.clear(); // Clear the buffer for reuse
+                endpoint.submit(completion.buffer); // Resubmit buffer to endpoint
+                match bytes.is_empty() {
+                    true => continue, // Status bytes only
+                    false => return Some((Ok(bytes), Some((endpoint, diagnostics)))),
+                }
+            }
+        });
+        Box::pin(stream)
+    }
+}
+
+impl Transport for Usb {
+    fn open(&mut self) -> BoxFuture<'_, io::Result<Incoming>> {
+        Box::pin(async move {
+            let interface = self
+                .device_info
+                .open()
+                .await?
+                .detach_and_claim_interface(0)
+                .await?;
+            serial_port::init(&interface).await?;
+            self.outgoing = Some(interface.endpoint(OUT_ENDPOINT)?);
+            let incoming = interface.endpoint(IN_ENDPOINT)?;
+            Ok(Self::listen(incoming, self.queue_depth, self.diagnostics()))
+        })
+    }
+
+    fn close(&mut self) -> BoxFuture<'_, io::Result<()>> {
+        Box::pin(async move {
+            self.outgoing = None; // Releases the Interface once the incoming stream is dropped
+            Ok(())
+        })
+    }
+
+    fn send(&mut self, bytes: Vec<u8>) -> BoxFuture<'_, io::Result<()>> {
+        Box::pin(async move {
+            let endpoint = self
+                .outgoing
+                .as_mut()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "USB closed"))?;
+            endpoint.submit(Buffer::from(bytes));
+            endpoint.next_complete().await.status?;
+            Ok(())
+        })
+    }
+
+    fn reconnect(&mut self) -> BoxFuture<'_, io::Result<()>> {
+        Box::pin(async move {
+            // Begin watching before searching, so that a device connected in between is not missed
+            let mut watch = watch_devices()?;
+            let is_match = |d: &DeviceInfo| d.serial_number() == Some(self.serial_number.as_str());
+            if let Some(device_info) = list_devices().await?.find(is_match) {
+                self.device_info = device_info;
+                return Ok(());
+            }
+            log::debug!("{self:?} RECONNECT (waiting)");
+            while let Some(event) = watch.next().await {
+                if let HotplugEvent::Connected(device_info) = event
+                    && is_match(&device_info)
+                {
+                    self.device_info = device_info;
+                    return Ok(());
+                }
+            }
+            Err(io::ErrorKind::UnexpectedEof.into())
+        })
+    }
+}
+
+impl Debug for Usb {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "USB {{ {} {:04X}:{:04X} }}",
+            self.device_info.serial_number().unwrap_or("?"),
+            self.device_info.vendor_id(),
+            self.device_info.product_id(),
+        )
+    }
+}
@@ -8,14 +8,13 @@ Redistribution and use in source and binary forms, with or without
 modification, are permitted provided that the conditions of the LICENSE are met.
 */
 
+use std::io;
 use std::time::Duration;
 
 use nusb::Interface;
 use nusb::transfer::{ControlOut, ControlType, Recipient};
 use smol::Timer;
 
-use crate::devices::abort;
-
 /// Control transfer to reset the USB device controller
 const RESET_CONTROLLER: ControlOut = ControlOut {
     control_type: ControlType::Vendor,
@@ -94,26 +93,44 @@ const RTS: ControlOut = ControlOut {
     data: &[],
 };
 
+/// Control transfer to set the FTDI latency timer to 1 ms.
+///
+/// The FTDI chip holds incoming bytes for up to 16 ms (default) before sending a short packet to
+/// the host. Most APT responses are shorter than a full packet, so this timer dominates the
+/// response latency unless it is reduced.
+const LATENCY_TIMER: ControlOut = ControlOut {
+    control_type: ControlType::Vendor,
+    recipient: Recipient::Device,
+    request: 0x09,
+    value: 0x0001, // 1 ms
+    index: 0,
+    data: &[],
+};
+
 /// Initializes serial port settings according to Thorlabs APT protocol requirements:
 /// - Baud rate 115200
 /// - Eight data bits
 /// - One stop bit
 /// - No parity
 /// - RTS/CTS flow control
-pub(super) async fn init(interface: &Interface) {
-    let control_out = async |control_out: ControlOut| {
+/// - 1 ms latency timer
+///
+/// Returns an [`io::Error`] if any control transfer fails.
+pub(super) async fn init(interface: &Interface) -> io::Result<()> {
+    let control_out = async |control_out: ControlOut| -> io::Result<()> {
         interface
             .control_out(control_out, Duration::from_millis(100))
             .await
-            .unwrap_or_else(|e| abort(format!("Control transfer failed : {}", e)))
+            .map_err(|e| io::Error::other(format!("Control transfer failed : {}", e)))
     };
-    control_out(RESET_CONTROLLER).await;
-    control_out(BAUD_RATE).await;
-    control_out(EIGHT_DATA_ONE_STOP_NO_PARITY).await;
+    control_out(RESET_CONTROLLER).await?;
+    control_out(BAUD_RATE).await?;
+    control_out(EIGHT_DATA_ONE_STOP_NO_PARITY).await?;
     Timer::after(Duration::from_millis(50)).await; // Pre-purge dwell 50 ms
-    control_out(PURGE_RX).await;
-    control_out(PURGE_TX).await;
+    control_out(PURGE_RX).await?;
+    control_out(PURGE_TX).await?;
     Timer::after(Duration::from_millis(50)).await; // Post-purge dwell 50 ms
-    control_out(FLOW_CONTROL).await;
-    control_out(RTS).await;
+    control_out(FLOW_CONTROL).await?;
+    control_out(RTS).await?;
+    control_out(LATENCY_TIMER).await
 }